@@ -0,0 +1,174 @@
+use comfy_table::{Table, Row};
+use wormhole_sdk::Chain;
+
+use crate::common::*;
+use crate::evm::{evm_contract, evm_guardian_set, evm_provider, CoreBridgeState};
+use crate::governance::{GOVERNANCE_CHAIN, GOVERNANCE_EMITTER};
+
+// mainnet guardian set 4, as published in guardianset/mainnetv2/v4.prototxt of the wormhole repo (set 3 with
+// RockawayX in place of Jump at index 0). update both when the guardians rotate, `bridge compare` reports chains
+// that are on another set
+pub const MAINNET_GUARDIAN_SET_INDEX: u32 = 4;
+pub const MAINNET_GUARDIAN_SET: [&str; 19] = [
+    "5893B5A76c3f739645648885bDCcC06cd70a3Cd3",
+    "fF6CB952589BDE862c25Ef4392132fb9D4A42157",
+    "114De8460193bdf3A2fCf81f86a09765F4762fD1",
+    "107A0086b32d7A0977926A205131d8731D39cbEB",
+    "8C82B2fd82FaeD2711d59AF0F2499D16e726f6b2",
+    "11b39756C042441BE6D8650b69b54EbE715E2343",
+    "54Ce5B4D348fb74B958e8966e2ec3dBd4958a7cd",
+    "15e7cAF07C4e3DC8e7C469f92C8Cd88FB8005a20",
+    "74a3bf913953D695260D88BC1aA25A4eeE363ef0",
+    "000aC0076727b35FBea2dAc28fEE5cCB0fEA768e",
+    "AF45Ced136b9D9e24903464AE889F5C8a723FC14",
+    "f93124b7c738843CBB89E864c862c38cddCccF95",
+    "D2CC37A4dc036a8D232b48f62cDD4731412f4890",
+    "DA798F6896A3331F64b48c12D1D57Fd9cbe70811",
+    "71AA1BE1D36CaFE3867910F99C09e347899C19C3",
+    "8192b6E7387CCd768277c17DAb1b7a5027c0b3Cf",
+    "178e21ad2E77AE06711549CFBB1f9c7a9d8096e8",
+    "5E1487F35515d02A92753504a8D75471b9f49EdB",
+    "6FbEBc898F403E4773E95feB15E80C9A99c8348d",
+];
+
+// the keys of a guardian set, from the bundled set or else from the ethereum core bridge, which keeps past sets too
+pub fn guardian_set(index: u32) -> Result<Option<Vec<[u8; 20]>>, CooError> {
+    if index == MAINNET_GUARDIAN_SET_INDEX {
+        let guardians = MAINNET_GUARDIAN_SET.iter().map(|g| {
+            let mut guardian = [0u8; 20];
+            guardian.copy_from_slice(&hextobytes(g)?);
+            Ok(guardian)
+        }).collect::<Result<Vec<[u8; 20]>, CooError>>()?;
+        return Ok(Some(guardians));
+    }
+    let chain = CooChain::Inner(Chain::Ethereum);
+    let (guardians, _) = evm_guardian_set(evm_provider(chain, None)?, evm_contract(chain, EmitterType::CoreBridge, None)?, index)?;
+    // sets that were never created have no keys
+    match guardians.is_empty() {
+        true => Ok(None),
+        false => Ok(Some(guardians)),
+    }
+}
+
+// every chain that we know the core bridge of
+pub fn core_bridge_chains() -> Vec<CooChain> {
+    let mut chains: Vec<CooChain> = EMITTERS.keys()
+        .filter(|(chain, emitter)| *emitter == EmitterType::CoreBridge && RPC_ENDPOINTS.contains_key(chain))
+        .map(|(chain, _)| *chain)
+        .collect();
+    chains.sort();
+    return chains;
+}
+
+fn guardian_set_matches(state: &CoreBridgeState, guardians: &[String]) -> bool {
+    state.guardians.len() == guardians.len()
+        && state.guardians.iter().zip(guardians).all(|(a, b)| hex::encode(a) == b.to_lowercase())
+}
+
+// the problems found on each chain, compared to the bundled guardian set and to the most recent state across chains
+pub fn compare_bridge_states(states: &[(CooChain, CoreBridgeState)]) -> Vec<(CooChain, Vec<String>)> {
+    let bundled: Vec<String> = MAINNET_GUARDIAN_SET.iter().map(|g| g.to_string()).collect();
+    let latest = states.iter().max_by_key(|(_, state)| state.guardian_set_index).map(|(_, state)| state);
+    states.iter().map(|(chain, state)| {
+        let mut issues = Vec::new();
+        if state.chain_id != u16::from(*chain) {
+            issues.push(format!("chain id is {}", state.chain_id));
+        }
+        // a chain on another set is either behind, or the bundled set is out of date
+        if state.guardian_set_index != MAINNET_GUARDIAN_SET_INDEX {
+            issues.push(format!("guardian set {} is not the bundled set {}", state.guardian_set_index, MAINNET_GUARDIAN_SET_INDEX));
+        } else if !guardian_set_matches(state, &bundled) {
+            issues.push(format!("guardian set {} differs from the bundled set", state.guardian_set_index));
+        }
+        if let Some(latest) = latest {
+            if state.guardian_set_index < latest.guardian_set_index {
+                issues.push(format!("guardian set {} is behind {}", state.guardian_set_index, latest.guardian_set_index));
+            } else if state.guardians != latest.guardians {
+                issues.push("guardian keys differ from other chains on the same set".to_string());
+            }
+        }
+        if state.governance_chain != u16::from(GOVERNANCE_CHAIN) || state.governance_contract != GOVERNANCE_EMITTER.0 {
+            issues.push("unexpected governance chain or contract".to_string());
+        }
+        (*chain, issues)
+    }).collect()
+}
+
+pub fn pretty_bridge_state(chain: CooChain, state: &CoreBridgeState) -> String {
+    let mut table = Table::new();
+    table.set_header([format!("Core Bridge State ({})", Chain::from(chain))]);
+    let guardians = state.guardians.iter().enumerate()
+        .map(|(i, g)| format!("{}: 0x{}", i, hex::encode(g)))
+        .collect::<Vec<String>>()
+        .join("\n");
+    let rows: Vec<Row> = vec![
+        ["Chain ID", &state.chain_id.to_string()].into(),
+        ["Guardian Set Index", &state.guardian_set_index.to_string()].into(),
+        ["Guardian Set Expiration", &state.guardian_set_expiration.to_string()].into(),
+        ["Guardians", &guardians].into(),
+        ["Message Fee", &state.message_fee.to_string()].into(),
+        ["Governance Chain", &state.governance_chain.to_string()].into(),
+        ["Governance Contract", &hex::encode(state.governance_contract)].into(),
+    ];
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+pub fn pretty_bridge_comparison(issues: &[(CooChain, Vec<String>)]) -> String {
+    let mut table = Table::new();
+    table.set_header(["Chain", "Status"]);
+    for (chain, issues) in issues {
+        let status = if issues.is_empty() { "ok".to_string() } else { issues.join("\n") };
+        table.add_row([Chain::from(*chain).to_string(), status]);
+    }
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::U256;
+    use super::*;
+
+    fn bridge_state(chain: Chain, guardian_set_index: u32, guardians: Vec<[u8; 20]>) -> (CooChain, CoreBridgeState) {
+        (CooChain::Inner(chain), CoreBridgeState {
+            chain_id: u16::from(chain),
+            guardian_set_index,
+            guardians,
+            guardian_set_expiration: 0,
+            message_fee: U256::zero(),
+            governance_chain: u16::from(GOVERNANCE_CHAIN),
+            governance_contract: GOVERNANCE_EMITTER.0,
+        })
+    }
+
+    #[test]
+    fn test_compare_bridge_states() {
+        let bundled = guardian_set(MAINNET_GUARDIAN_SET_INDEX).unwrap().unwrap();
+        let mut swapped = bundled.clone();
+        swapped.swap(0, 1);
+
+        let mut states = vec![
+            bridge_state(Chain::Ethereum, MAINNET_GUARDIAN_SET_INDEX, bundled.clone()),
+            bridge_state(Chain::Bsc, MAINNET_GUARDIAN_SET_INDEX - 1, bundled.clone()),
+            bridge_state(Chain::Polygon, MAINNET_GUARDIAN_SET_INDEX, swapped),
+            bridge_state(Chain::Avalanche, MAINNET_GUARDIAN_SET_INDEX, bundled.clone()),
+        ];
+        states[3].1.governance_contract = [0u8; 32];
+
+        let issues = compare_bridge_states(&states);
+        assert!(issues[0].1.is_empty());
+        assert_eq!(issues[1].1, vec![
+            format!("guardian set {} is not the bundled set {}", MAINNET_GUARDIAN_SET_INDEX - 1, MAINNET_GUARDIAN_SET_INDEX),
+            format!("guardian set {} is behind {}", MAINNET_GUARDIAN_SET_INDEX - 1, MAINNET_GUARDIAN_SET_INDEX),
+        ]);
+        assert_eq!(issues[2].1, vec![
+            format!("guardian set {} differs from the bundled set", MAINNET_GUARDIAN_SET_INDEX),
+            "guardian keys differ from other chains on the same set".to_string(),
+        ]);
+        assert_eq!(issues[3].1, vec!["unexpected governance chain or contract".to_string()]);
+
+        // chains that moved past the bundled set are reported even when they agree with each other
+        let issues = compare_bridge_states(&[bridge_state(Chain::Ethereum, MAINNET_GUARDIAN_SET_INDEX + 1, bundled)]);
+        assert_eq!(issues[0].1, vec![format!("guardian set {} is not the bundled set {}", MAINNET_GUARDIAN_SET_INDEX + 1, MAINNET_GUARDIAN_SET_INDEX)]);
+    }
+}
@@ -0,0 +1,133 @@
+use comfy_table::{Table, Row};
+use wormhole_sdk::{Address, Amount, Chain};
+
+use crate::common::*;
+
+// payload id of the wormhole circle integration deposit with payload message
+const DEPOSIT_WITH_PAYLOAD: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CctpDeposit {
+    pub token: Address,
+    pub amount: Amount,
+    pub source_domain: u32,
+    pub target_domain: u32,
+    pub nonce: u64,
+    pub burn_source: Address,
+    pub mint_recipient: Address,
+    pub payload: Vec<u8>,
+}
+
+pub fn cctp_domain_name(domain: u32) -> &'static str {
+    match domain {
+        0 => "ethereum",
+        1 => "avalanche",
+        2 => "optimism",
+        3 => "arbitrum",
+        4 => "noble",
+        5 => "solana",
+        6 => "base",
+        7 => "polygon",
+        8 => "sui",
+        9 => "aptos",
+        _ => "unknown",
+    }
+}
+
+// the wormhole chain of a cctp domain, for the domains that wormhole also connects
+pub fn cctp_domain_chain(domain: u32) -> Option<Chain> {
+    let chain: u16 = match domain {
+        0 => 2,
+        1 => 6,
+        2 => 24,
+        3 => 23,
+        5 => 1,
+        6 => 30,
+        7 => 5,
+        8 => 21,
+        9 => 22,
+        _ => return None,
+    };
+    Some(Chain::from(chain))
+}
+
+pub fn decode_cctp_payload(payload: &[u8]) -> Result<CctpDeposit, CooError> {
+    let mut reader = PayloadReader::new(payload);
+    let payload_id = reader.read_u8()?;
+    if payload_id != DEPOSIT_WITH_PAYLOAD {
+        return Err(CooError::ParseError(format!("unknown cctp payload id: {}", payload_id)));
+    }
+    let token = reader.read_address()?;
+    let amount = reader.read_amount()?;
+    let source_domain = reader.read_u32()?;
+    let target_domain = reader.read_u32()?;
+    let nonce = reader.read_u64()?;
+    let burn_source = reader.read_address()?;
+    let mint_recipient = reader.read_address()?;
+    let payload_len = reader.read_u16()? as usize;
+    let payload = reader.read_bytes(payload_len)?.to_vec();
+    if !reader.is_empty() {
+        return Err(CooError::ParseError("trailing bytes after cctp payload".to_string()));
+    }
+    return Ok(CctpDeposit { token, amount, source_domain, target_domain, nonce, burn_source, mint_recipient, payload });
+}
+
+pub fn pretty_cctp_payload(deposit: &CctpDeposit) -> String {
+    let mut table = Table::new();
+    table.set_header(["Wormhole CCTP Payload Information"]);
+    let rows: Vec<Row> = vec![
+        ["Payload Type", "DepositWithPayload"].into(),
+        ["Token", &deposit.token.to_string()].into(),
+        ["Amount", &amounttodecimal(&deposit.amount)].into(),
+        ["Source Domain", &format!("{} ({})", deposit.source_domain, cctp_domain_name(deposit.source_domain))].into(),
+        ["Target Domain", &format!("{} ({})", deposit.target_domain, cctp_domain_name(deposit.target_domain))].into(),
+        ["CCTP Nonce", &deposit.nonce.to_string()].into(),
+        ["Burn Source", &deposit.burn_source.to_string()].into(),
+        ["Mint Recipient", &deposit.mint_recipient.to_string()].into(),
+        ["Payload", &hex::encode(&deposit.payload)].into(),
+    ];
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_cctp_payload(deposit: &CctpDeposit) -> Vec<u8> {
+        let mut payload = vec![DEPOSIT_WITH_PAYLOAD];
+        payload.extend_from_slice(&deposit.token.0);
+        payload.extend_from_slice(&deposit.amount.0);
+        payload.extend_from_slice(&deposit.source_domain.to_be_bytes());
+        payload.extend_from_slice(&deposit.target_domain.to_be_bytes());
+        payload.extend_from_slice(&deposit.nonce.to_be_bytes());
+        payload.extend_from_slice(&deposit.burn_source.0);
+        payload.extend_from_slice(&deposit.mint_recipient.0);
+        payload.extend_from_slice(&(deposit.payload.len() as u16).to_be_bytes());
+        payload.extend_from_slice(&deposit.payload);
+        payload
+    }
+
+    #[test]
+    fn test_decode_cctp_payload() {
+        // 25 USDC burned on ethereum for avalanche, with the layout of the circle integration deposits on mainnet
+        let deposit = CctpDeposit {
+            token: hextoaddress("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap(),
+            amount: stringtoamount("25000000").unwrap(),
+            source_domain: 0,
+            target_domain: 1,
+            nonce: 9517,
+            burn_source: hextoaddress("aada05bd399372f0b0463744c09113c137636f6a").unwrap(),
+            mint_recipient: hextoaddress("09fb06a271faff70a651047395aaeb6265265f13").unwrap(),
+            payload: vec![0x01, 0x02, 0x03],
+        };
+        let payload = encode_cctp_payload(&deposit);
+        assert_eq!(payload.len(), 1 + 32 + 32 + 4 + 4 + 8 + 32 + 32 + 2 + 3);
+        assert_eq!(decode_cctp_payload(&payload).unwrap(), deposit);
+        assert_eq!(cctp_domain_name(deposit.target_domain), "avalanche");
+
+        let mut trailing = payload.clone();
+        trailing.push(0);
+        assert!(decode_cctp_payload(&trailing).is_err());
+    }
+}
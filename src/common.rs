@@ -4,7 +4,7 @@ use clap::ValueEnum;
 use comfy_table::Table;
 use lazy_static::lazy_static;
 use serde_wormhole::RawMessage;
-use wormhole_sdk::{Chain, token::Message, nft::TokenId};
+use wormhole_sdk::{Address, Amount, Chain, token::Message, nft::TokenId};
 
 pub const GUARDIAN_URL: &str = "https://wormhole-v2-mainnet-api.certus.one/";
 
@@ -15,8 +15,15 @@ lazy_static! {
             ((CooChain::Inner(Chain::Ethereum), EmitterType::TokenBridge), "3ee18B2214AFF97000D974cf647E7C347E8fa585"),
             ((CooChain::Inner(Chain::Ethereum), EmitterType::NftBridge), "6FFd7EdE62328b3Af38FCD61461Bbfc52F5651fE"),
             ((CooChain::Inner(Chain::Avalanche), EmitterType::CoreBridge), "54a8e5f9c4CbA08F9943965859F6c34eAF03E26c"),
-            ((CooChain::Inner(Chain::Avalanche), EmitterType::TokenBridge), "e082f06ff657d94310cb8ce8b0d9a04541d8052"),
+            ((CooChain::Inner(Chain::Avalanche), EmitterType::TokenBridge), "0e082F06FF657D94310cB8cE8B0D9a04541d8052"),
             ((CooChain::Inner(Chain::Avalanche), EmitterType::NftBridge), "f7B6737Ca9c4e08aE573F75A97B73D7a813f5De5"),
+            ((CooChain::Inner(Chain::Ethereum), EmitterType::WormholeRelayer), "27428DD2d3DD32A4D7f7C497eAaa23130d894911"),
+            ((CooChain::Inner(Chain::Avalanche), EmitterType::WormholeRelayer), "27428DD2d3DD32A4D7f7C497eAaa23130d894911"),
+            ((CooChain::Inner(Chain::Ethereum), EmitterType::CctpIntegration), "AaDA05BD399372f0b0463744C09113c137636f6a"),
+            ((CooChain::Inner(Chain::Avalanche), EmitterType::CctpIntegration), "09Fb06A271faFf70A651047395AaEb6265265F13"),
+            ((CooChain::Inner(Chain::Solana), EmitterType::Pyth), "6bb14509a612f01fbbc4cffeebd4bbfb492a86df717ebe92eb6df432a3f00a25"),
+            // pythnet
+            ((CooChain::from(26u16), EmitterType::Pyth), "e101faedac5851e32b9b23b5f9411a8c2bac4aae3ed4dd7b811dd1a72ea4aa71"),
         ]
     );
     pub static ref RPC_ENDPOINTS: HashMap<CooChain, &'static str> = HashMap::from(
@@ -37,6 +44,9 @@ pub enum EmitterType {
     CoreBridge,
     TokenBridge,
     NftBridge,
+    WormholeRelayer,
+    CctpIntegration,
+    Pyth,
     Address([u8; 32]),
 }
 
@@ -47,6 +57,9 @@ impl From<&str> for EmitterType {
             "core" => EmitterType::CoreBridge,
             "token" => EmitterType::TokenBridge,
             "nft" => EmitterType::NftBridge,
+            "relayer" => EmitterType::WormholeRelayer,
+            "cctp" => EmitterType::CctpIntegration,
+            "pyth" => EmitterType::Pyth,
             _ => { 
                 let mut emitter_address = [0u8; 32];
                 let decoded = hextobytes(s).unwrap();
@@ -65,6 +78,9 @@ impl Display for EmitterType {
             EmitterType::CoreBridge => write!(f, "core"),
             EmitterType::TokenBridge => write!(f, "token"),
             EmitterType::NftBridge => write!(f, "nft"),
+            EmitterType::WormholeRelayer => write!(f, "relayer"),
+            EmitterType::CctpIntegration => write!(f, "cctp"),
+            EmitterType::Pyth => write!(f, "pyth"),
             EmitterType::Address(a) => write!(f, "{}", hex::encode(a)),
         }
     }
@@ -85,6 +101,17 @@ pub enum PayloadType {
     WormholeTokenTransferPayload,
     WormholeNftTransfer,
     WormholeAssetMeta,
+    WormholeRelayerDelivery,
+    WormholeRelayerRedelivery,
+    WormholeCctpDeposit,
+    WormholeNttTransceiverMessage,
+    WormholeNttTransceiverInit,
+    WormholeNttTransceiverRegistration,
+    PythBatchPriceAttestation,
+    PythAccumulatorUpdate,
+    WormholeGatewayTransfer,
+    CustomSchema,
+    WasmPlugin,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -94,6 +121,17 @@ pub enum PayloadResponse {
     WormholeTokenTransferPayload(wormhole_sdk::token::Message<Box<RawMessage>>),
     WormholeAssetMeta(wormhole_sdk::token::Message<Box<RawMessage>>),
     WormholeNftTransfer(wormhole_sdk::nft::Message),
+    WormholeRelayerDelivery(crate::relayer::RelayerMessage),
+    WormholeRelayerRedelivery(crate::relayer::RelayerMessage),
+    WormholeCctpDeposit(crate::cctp::CctpDeposit),
+    WormholeNttTransceiverMessage(crate::ntt::NttMessage),
+    WormholeNttTransceiverInit(crate::ntt::NttMessage),
+    WormholeNttTransceiverRegistration(crate::ntt::NttMessage),
+    PythBatchPriceAttestation(crate::pyth::PythMessage),
+    PythAccumulatorUpdate(crate::pyth::PythMessage),
+    WormholeGatewayTransfer(wormhole_sdk::token::Message<Box<RawMessage>>),
+    CustomSchema(crate::schema::SchemaMessage),
+    WasmPlugin(crate::schema::SchemaMessage),
 }
 
 impl Display for PayloadResponse {
@@ -102,6 +140,7 @@ impl Display for PayloadResponse {
             PayloadResponse::RawBytes(b) => write!(f, "{}", hex::encode(b)),
             PayloadResponse::WormholeTokenTransfer(m) |
             PayloadResponse::WormholeTokenTransferPayload(m) |
+            PayloadResponse::WormholeGatewayTransfer(m) |
             PayloadResponse::WormholeAssetMeta(m) => {
                 let table = match m {
                     Message::Transfer { amount, token_address, token_chain, recipient, recipient_chain, fee } => {
@@ -147,6 +186,16 @@ impl Display for PayloadResponse {
                 write!(f, "{}", table)
             }
             PayloadResponse::WormholeNftTransfer(m) => write!(f, "{}", serde_json::to_string_pretty(m).unwrap()),
+            PayloadResponse::WormholeRelayerDelivery(m) |
+            PayloadResponse::WormholeRelayerRedelivery(m) => write!(f, "{}", crate::relayer::pretty_relayer_payload(m, None)),
+            PayloadResponse::WormholeCctpDeposit(m) => write!(f, "{}", crate::cctp::pretty_cctp_payload(m)),
+            PayloadResponse::WormholeNttTransceiverMessage(m) |
+            PayloadResponse::WormholeNttTransceiverInit(m) |
+            PayloadResponse::WormholeNttTransceiverRegistration(m) => write!(f, "{}", crate::ntt::pretty_ntt_payload(m)),
+            PayloadResponse::PythBatchPriceAttestation(m) |
+            PayloadResponse::PythAccumulatorUpdate(m) => write!(f, "{}", crate::pyth::pretty_pyth_payload(m)),
+            PayloadResponse::CustomSchema(m) |
+            PayloadResponse::WasmPlugin(m) => write!(f, "{}", crate::schema::pretty_schema_payload(m)),
         }
     }
 }
@@ -181,6 +230,14 @@ impl From<u16> for CooChain {
     }
 }
 
+impl From<CooChain> for Chain {
+    fn from(c: CooChain) -> Self {
+        match c {
+            CooChain::Inner(c) => c,
+        }
+    }
+}
+
 impl From<CooChain> for u16 {
     fn from(c: CooChain) -> Self {
         match c {
@@ -197,6 +254,13 @@ pub enum CooError {
     Base58Error(bs58::decode::Error),
     HexError(hex::FromHexError),
     SerdeWormholeError(serde_wormhole::Error),
+    WalletError(ethers::signers::WalletError),
+    IoError(std::io::Error),
+    WasmError(wasmi::Error),
+    ProviderError(ethers::providers::ProviderError),
+    ContractError(String),
+    // the guardian answered, but does not have the VAA
+    VaaNotFound(String),
     ParseError(String),
 }
 
@@ -230,6 +294,37 @@ impl From<serde_wormhole::Error> for CooError {
     }
 }
 
+impl From<ethers::signers::WalletError> for CooError {
+    fn from(e: ethers::signers::WalletError) -> Self {
+        CooError::WalletError(e)
+    }
+}
+
+impl From<std::io::Error> for CooError {
+    fn from(e: std::io::Error) -> Self {
+        CooError::IoError(e)
+    }
+}
+
+impl From<wasmi::Error> for CooError {
+    fn from(e: wasmi::Error) -> Self {
+        CooError::WasmError(e)
+    }
+}
+
+impl From<ethers::providers::ProviderError> for CooError {
+    fn from(e: ethers::providers::ProviderError) -> Self {
+        CooError::ProviderError(e)
+    }
+}
+
+impl<M: ethers::providers::Middleware> From<ethers::contract::ContractError<M>> for CooError {
+    fn from(e: ethers::contract::ContractError<M>) -> Self {
+        // the error is generic over the middleware, so we only keep its message
+        CooError::ContractError(e.to_string())
+    }
+}
+
 impl From<bs58::decode::Error> for CooError {
     fn from(e: bs58::decode::Error) -> Self {
         CooError::Base58Error(e)
@@ -247,11 +342,19 @@ pub fn base64tobytes(s: &str) -> Result<Vec<u8>, CooError> {
     Ok(s)
 }
 
+pub fn bytestobase64(s: &[u8]) -> String {
+    general_purpose::STANDARD.encode(s)
+}
+
 pub fn base58tobytes(s: &str) -> Result<Vec<u8>, CooError> {
     let s = bs58::decode(s).into_vec()?;
     Ok(s)
 }
 
+pub fn bytestobase58(s: &[u8]) -> String {
+    bs58::encode(s).into_string()
+}
+
 pub fn hextobytes(s: &str) -> Result<Vec<u8>, CooError> {
     // if string has 0x, remove it
     let s = if s.starts_with("0x") {
@@ -276,10 +379,64 @@ pub fn bytestohex(s: &[u8]) -> String {
     format!("0x{}", hex::encode(&s[leading_zeros..]))
 }
 
+// the guardian rest api encodes uint64 as strings and uint32 as numbers
+pub fn json_u64(value: &serde_json::Value) -> u64 {
+    match value {
+        serde_json::Value::String(s) => s.parse().unwrap_or_default(),
+        v => v.as_u64().unwrap_or_default(),
+    }
+}
+
+// chain ids are uint32 in the guardian rest api, values that don't fit a u16 are an error instead of being truncated
+pub fn json_u16(value: &serde_json::Value) -> Result<u16, CooError> {
+    let n = match value {
+        serde_json::Value::String(s) => s.parse::<u64>().ok(),
+        v => v.as_u64(),
+    };
+    n.and_then(|n| u16::try_from(n).ok())
+        .ok_or_else(|| CooError::ParseError(format!("not a chain id: {}", value)))
+}
+
+pub fn json_string(value: &serde_json::Value) -> String {
+    value.as_str().unwrap_or_default().to_string()
+}
+
+pub fn hextoaddress(s: &str) -> Result<Address, CooError> {
+    // addresses shorter than 32 bytes (e.g. evm addresses) are left-padded with 0s
+    let decoded = hextobytes(s)?;
+    if decoded.len() > 32 {
+        return Err(CooError::ParseError(format!("address longer than 32 bytes: {}", s)));
+    }
+    let mut address = [0u8; 32];
+    let diff = 32 - decoded.len();
+    address[diff..].copy_from_slice(&decoded);
+    Ok(Address(address))
+}
+
+// like CooChain::from(&str), but an unknown chain is an error instead of a panic
+pub fn stringtochain(s: &str) -> Result<CooChain, CooError> {
+    if let Ok(c) = s.parse::<Chain>() {
+        return Ok(CooChain::Inner(c));
+    }
+    let c = s.parse::<u16>().map_err(|_| CooError::ParseError(format!("unknown chain: {}", s)))?;
+    Ok(CooChain::from(c))
+}
+
+pub fn stringtoamount(s: &str) -> Result<Amount, CooError> {
+    // amounts are given either as a 0x prefixed hex string or as a decimal string
+    if s.starts_with("0x") {
+        return Ok(Amount(hextoaddress(s)?.0));
+    }
+    let amount = ethers::types::U256::from_dec_str(s).map_err(|e| CooError::ParseError(format!("{:?}", e)))?;
+    let mut bytes = [0u8; 32];
+    amount.to_big_endian(&mut bytes);
+    Ok(Amount(bytes))
+}
+
 pub fn resolve_emitter_address(chain: CooChain, emitter: EmitterType) -> Result<String, CooError> {
     match emitter {
         EmitterType::Unset => Err(CooError::ParseError("Unset emitter type".to_string())),
-        EmitterType::CoreBridge | EmitterType::TokenBridge | EmitterType::NftBridge  =>  {
+        EmitterType::CoreBridge | EmitterType::TokenBridge | EmitterType::NftBridge | EmitterType::WormholeRelayer | EmitterType::CctpIntegration | EmitterType::Pyth  =>  {
             let contract_string = EMITTERS[&(chain, emitter)];
             let contract_address= hextobytes(contract_string)?;
             let wormhole_padded = format!("{:0>64}", hex::encode(contract_address));
@@ -289,10 +446,130 @@ pub fn resolve_emitter_address(chain: CooChain, emitter: EmitterType) -> Result<
     }
 }
 
+// sequential big-endian reader over payload bytes, used by the decoders for non-serde payload formats
+pub struct PayloadReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> PayloadReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        PayloadReader { data, offset: 0 }
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], CooError> {
+        if self.data.len() - self.offset < len {
+            return Err(CooError::ParseError(format!("payload too short: wanted {} bytes at offset {}, have {}", len, self.offset, self.data.len())));
+        }
+        let bytes = &self.data[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], CooError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, CooError> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, CooError> {
+        Ok(u16::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, CooError> {
+        Ok(u32::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, CooError> {
+        Ok(u64::from_be_bytes(self.read_array()?))
+    }
+
+    // solana accounts are borsh encoded, which is little-endian
+    pub fn read_u16_le(&mut self) -> Result<u16, CooError> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u32_le(&mut self) -> Result<u32, CooError> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u64_le(&mut self) -> Result<u64, CooError> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, CooError> {
+        Ok(i32::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_i64(&mut self) -> Result<i64, CooError> {
+        Ok(i64::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_address(&mut self) -> Result<Address, CooError> {
+        Ok(Address(self.read_array()?))
+    }
+
+    pub fn read_amount(&mut self) -> Result<Amount, CooError> {
+        Ok(Amount(self.read_array()?))
+    }
+
+    pub fn read_remaining(&mut self) -> &'a [u8] {
+        let bytes = &self.data[self.offset..];
+        self.offset = self.data.len();
+        bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offset == self.data.len()
+    }
+}
+
 pub fn tokenidtostring(tokenid: &TokenId) -> String {
     bytestohex(&tokenid.0)
 }
 
 pub fn amounttostring(amount: &wormhole_sdk::Amount) -> String {
     bytestohex(&amount.0)
-}
\ No newline at end of file
+}
+
+pub fn amounttodecimal(amount: &wormhole_sdk::Amount) -> String {
+    ethers::types::U256::from_big_endian(&amount.0).to_string()
+}
+
+pub fn trimmedamounttostring(amount: u64, decimals: u8) -> String {
+    // the amount is an integer scaled by 10^decimals, so we just place the decimal point
+    let digits = format!("{:0>width$}", amount, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trimmedamounttostring() {
+        assert_eq!(trimmedamounttostring(12345, 2), "123.45");
+        assert_eq!(trimmedamounttostring(5, 3), "0.005");
+        assert_eq!(trimmedamounttostring(42, 0), "42");
+    }
+
+    #[test]
+    fn test_json_u64() {
+        // grpc-gateway encodes uint64 as strings and uint32 as numbers
+        assert_eq!(json_u64(&serde_json::json!("1690000000")), 1690000000);
+        assert_eq!(json_u64(&serde_json::json!(42)), 42);
+        assert_eq!(json_u64(&serde_json::Value::Null), 0);
+        assert_eq!(json_u16(&serde_json::json!(2)).unwrap(), 2);
+        assert!(json_u16(&serde_json::json!(65538)).is_err());
+        assert!(json_u16(&serde_json::Value::Null).is_err());
+    }
+}
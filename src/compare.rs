@@ -0,0 +1,195 @@
+use comfy_table::{Cell, Color, Table};
+use reqwest::Url;
+use serde_wormhole::RawMessage;
+use wormhole_sdk::Vaa;
+
+use crate::common::*;
+use crate::signer::{recover_guardian, vaa_body, vaa_digest};
+use crate::vaa::{parse_vaa, query_guardian};
+
+// what one guardian endpoint returned for the VAA
+#[derive(Debug)]
+pub struct VaaCopy {
+    pub url: Url,
+    pub vaa_bytes: Result<Vec<u8>, CooError>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CopySummary {
+    pub url: Url,
+    pub body_hash: Option<[u8; 32]>,
+    pub guardian_set_index: Option<u32>,
+    // signature index and whether it was signed by the guardian at that index
+    pub signatures: Vec<(u8, bool)>,
+    pub error: Option<String>,
+}
+
+// endpoints are queried in parallel, a slow node should not hold up the others
+pub fn query_guardians(chain: CooChain, emitter: EmitterType, sequence: u64, guardian_urls: &[Url]) -> Vec<VaaCopy> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = guardian_urls.iter().map(|url| {
+            scope.spawn(move || VaaCopy { url: url.clone(), vaa_bytes: query_guardian(chain, emitter, sequence, url.clone()) })
+        }).collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+}
+
+// without the guardian set the signatures are only recovered, not matched against the keys
+fn signature_valid(digest: &[u8; 32], signature: &wormhole_sdk::vaa::Signature, guardians: Option<&[[u8; 20]]>) -> bool {
+    match (recover_guardian(digest, signature), guardians) {
+        (Ok(recovered), Some(guardians)) => guardians.get(signature.index as usize) == Some(&recovered),
+        (Ok(_), None) => true,
+        (Err(_), _) => false,
+    }
+}
+
+fn summarize_copy(copy: &VaaCopy, guardians: Option<&[[u8; 20]]>) -> Result<CopySummary, CooError> {
+    let vaa_bytes = match &copy.vaa_bytes {
+        Ok(vaa_bytes) => vaa_bytes,
+        Err(e) => return Ok(CopySummary { url: copy.url.clone(), body_hash: None, guardian_set_index: None, signatures: Vec::new(), error: Some(format!("{:?}", e)) }),
+    };
+    let vaa = parse_vaa(vaa_bytes)?;
+    let digest = vaa_digest(&vaa)?;
+    let signatures = vaa.signatures.iter().map(|s| (s.index, signature_valid(&digest, s, guardians))).collect();
+    Ok(CopySummary { url: copy.url.clone(), body_hash: Some(digest), guardian_set_index: Some(vaa.guardian_set_index), signatures, error: None })
+}
+
+pub fn summarize_copies(copies: &[VaaCopy], guardians: Option<&[[u8; 20]]>) -> Vec<CopySummary> {
+    copies.iter().map(|copy| summarize_copy(copy, guardians).unwrap_or_else(|e| CopySummary {
+        url: copy.url.clone(), body_hash: None, guardian_set_index: None, signatures: Vec::new(), error: Some(format!("{:?}", e)),
+    })).collect()
+}
+
+// every endpoint that answered signed the same body
+pub fn bodies_match(summaries: &[CopySummary]) -> bool {
+    let mut hashes = summaries.iter().filter_map(|s| s.body_hash);
+    match hashes.next() {
+        Some(first) => hashes.all(|h| h == first),
+        None => false,
+    }
+}
+
+// the first VAA with the valid signatures of every copy of the same body and guardian set, ordered by guardian index.
+// the keys are required, a recovered signature is not proof that a guardian of the set signed
+pub fn merge_signatures(copies: &[VaaCopy], guardians: &[[u8; 20]]) -> Result<Vec<u8>, CooError> {
+    // a node may return garbage, that should not keep the signatures of the others from being merged
+    let parsed: Vec<Vaa<&RawMessage>> = copies.iter()
+        .filter_map(|c| c.vaa_bytes.as_ref().ok())
+        .filter_map(|vaa_bytes| parse_vaa(vaa_bytes).ok())
+        .collect();
+    let first = match parsed.first() {
+        Some(vaa) => vaa,
+        None => return Err(CooError::ParseError("no endpoint returned the VAA".to_string())),
+    };
+    let digest = vaa_digest(first)?;
+    let body = vaa_body(first)?;
+    let guardian_set_index = first.guardian_set_index;
+    let mut signatures = Vec::new();
+    for vaa in &parsed {
+        // the guardian set index is not part of the signed body, signatures of another set can not be mixed in
+        if vaa.guardian_set_index != guardian_set_index || vaa_body(vaa)? != body {
+            continue;
+        }
+        signatures.extend(vaa.signatures.iter().filter(|s| signature_valid(&digest, s, Some(guardians))).cloned());
+    }
+    signatures.sort_by_key(|s| s.index);
+    signatures.dedup_by_key(|s| s.index);
+    let mut merged = parsed.into_iter().next().unwrap();
+    merged.signatures = signatures;
+    return Ok(serde_wormhole::to_vec(&merged)?);
+}
+
+// the number of signatures needed for a guardian set of the given size
+pub fn quorum(guardian_count: usize) -> usize {
+    guardian_count * 2 / 3 + 1
+}
+
+pub fn pretty_vaa_comparison(summaries: &[CopySummary], guardians: Option<&[[u8; 20]]>) -> String {
+    let first_hash = summaries.iter().find_map(|s| s.body_hash);
+    let mut table = Table::new();
+    table.set_header(["Guardian URL", "Body Hash", "Guardian Set", "Signatures"]);
+    for summary in summaries {
+        if let Some(error) = &summary.error {
+            table.add_row(vec![Cell::new(&summary.url), Cell::new(error).fg(Color::Red), Cell::new(""), Cell::new("")]);
+            continue;
+        }
+        let body_hash = Cell::new(summary.body_hash.map(hex::encode).unwrap_or_default());
+        let body_hash = match summary.body_hash == first_hash {
+            true => body_hash,
+            false => body_hash.fg(Color::Red),
+        };
+        let signatures = summary.signatures.iter()
+            .map(|(index, valid)| if *valid { index.to_string() } else { format!("{} (invalid)", index) })
+            .collect::<Vec<String>>()
+            .join(", ");
+        let count = summary.signatures.iter().filter(|(_, valid)| *valid).count();
+        let signatures = match guardians {
+            Some(guardians) => format!("{}/{}: {}", count, quorum(guardians.len()), signatures),
+            None => format!("{} (not checked against the guardian keys): {}", count, signatures),
+        };
+        table.add_row(vec![
+            Cell::new(&summary.url),
+            body_hash,
+            Cell::new(summary.guardian_set_index.map(|i| i.to_string()).unwrap_or_default()),
+            Cell::new(signatures),
+        ]);
+    }
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::signers::{LocalWallet, Signer};
+    use crate::signer::{parse_guardian_key, sign_vaa, DEVNET_GUARDIAN_KEY};
+    use crate::vaa::{build_vaa, test_vaa_header, CooVaaHeader};
+    use super::*;
+
+    fn summary(body_hash: Option<[u8; 32]>) -> CopySummary {
+        CopySummary { url: Url::parse("http://localhost/").unwrap(), body_hash, guardian_set_index: None, signatures: Vec::new(), error: None }
+    }
+
+    #[test]
+    fn test_bodies_match() {
+        // endpoints that failed to answer do not count as a mismatch
+        assert!(bodies_match(&[summary(Some([1; 32])), summary(None), summary(Some([1; 32]))]));
+        assert!(!bodies_match(&[summary(Some([1; 32])), summary(Some([2; 32]))]));
+        assert!(!bodies_match(&[summary(None)]));
+        assert_eq!(quorum(19), 13);
+    }
+
+    fn signed_copy(guardian_set_index: u32, keys: &[LocalWallet], keep: &[u8]) -> VaaCopy {
+        let payload = [0xde, 0xad, 0xbe, 0xef];
+        let mut vaa = build_vaa(&CooVaaHeader { guardian_set_index, ..test_vaa_header() }, &payload);
+        sign_vaa(&mut vaa, keys).unwrap();
+        vaa.signatures.retain(|s| keep.contains(&s.index));
+        VaaCopy { url: Url::parse("http://localhost/").unwrap(), vaa_bytes: Ok(serde_wormhole::to_vec(&vaa).unwrap()) }
+    }
+
+    #[test]
+    fn test_merge_signatures() {
+        let keys = vec![
+            parse_guardian_key(DEVNET_GUARDIAN_KEY).unwrap(),
+            parse_guardian_key(&"11".repeat(32)).unwrap(),
+        ];
+        let guardians: Vec<[u8; 20]> = keys.iter().map(|k| k.address().0).collect();
+        let merged_indexes = |copies: &[VaaCopy], guardians: &[[u8; 20]]| -> Vec<u8> {
+            let vaa_bytes = merge_signatures(copies, guardians).unwrap();
+            parse_vaa(&vaa_bytes).unwrap().signatures.iter().map(|s| s.index).collect()
+        };
+
+        // failed and unparsable copies are skipped
+        let copies = vec![
+            VaaCopy { url: Url::parse("http://localhost/").unwrap(), vaa_bytes: Err(CooError::VaaNotFound("not found".to_string())) },
+            VaaCopy { url: Url::parse("http://localhost/").unwrap(), vaa_bytes: Ok(vec![1, 2, 3]) },
+            signed_copy(0, &keys, &[0]),
+            signed_copy(0, &keys, &[1]),
+        ];
+        assert_eq!(merged_indexes(&copies, &guardians), vec![0, 1]);
+        // signatures of a key at another index of the set are not merged
+        assert_eq!(merged_indexes(&copies, &[guardians[0], guardians[0]]), vec![0]);
+        // copies of another guardian set sign the same body but are not merged
+        let copies = vec![signed_copy(0, &keys, &[0]), signed_copy(1, &keys, &[1])];
+        assert_eq!(merged_indexes(&copies, &guardians), vec![0]);
+        assert!(merge_signatures(&copies[..0], &guardians).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+use comfy_table::{Table, Row};
+use ethers::types::Address as EvmAddress;
+use reqwest::Url;
+use wormhole_sdk::Chain;
+
+use crate::common::*;
+use crate::evm::{evm_contract, evm_find_message, evm_next_sequence, evm_observable_block, evm_provider};
+use crate::governor::{find_enqueued_vaa, is_vaa_enqueued, EnqueuedVaa};
+
+// how far back the source chain is searched for the message
+const MESSAGE_LOOKBACK_BLOCKS: u64 = 10_000;
+
+// why a VAA is not served by the guardians, most likely cause first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotFoundCause {
+    Enqueued(String),
+    NotPublished(String),
+    NotFinalized(String),
+    NoQuorum,
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
+pub struct NotFoundDiagnosis {
+    pub checks: Vec<(&'static str, String)>,
+    pub cause: NotFoundCause,
+    pub enqueued: Option<EnqueuedVaa>,
+}
+
+// what the emitter is known as. this is only a hint, contracts like the relayer are deployed at the
+// same address on many chains and EMITTERS does not list every deployment
+fn check_emitter(chain: CooChain, emitter_address: &str) -> String {
+    let emitter_address = format!("{:0>64}", emitter_address).to_lowercase();
+    let mut known: Vec<&(CooChain, EmitterType)> = EMITTERS.iter()
+        .filter(|(_, address)| format!("{:0>64}", address).to_lowercase() == emitter_address)
+        .map(|(key, _)| key)
+        .collect();
+    known.sort_by_key(|(c, _)| u16::from(*c));
+    if let Some((_, emitter)) = known.iter().find(|(c, _)| *c == chain) {
+        return format!("{} emitter of {}", emitter, Chain::from(chain));
+    }
+    match known.first() {
+        Some((_, emitter)) => {
+            let chains = known.iter().map(|(c, _)| Chain::from(*c).to_string()).collect::<Vec<String>>().join(", ");
+            format!("{} emitter of {}, not known on {}", emitter, chains, Chain::from(chain))
+        },
+        None => "not a known emitter".to_string(),
+    }
+}
+
+fn check_governor(guardian_url: &Url, chain: CooChain, emitter_address: &str, sequence: u64) -> (String, Option<NotFoundCause>, Option<EnqueuedVaa>) {
+    match is_vaa_enqueued(guardian_url, chain, emitter_address, sequence) {
+        Ok(true) => {
+            let entry = find_enqueued_vaa(guardian_url, chain, emitter_address, sequence).ok().flatten();
+            let finding = match &entry {
+                Some(entry) => format!("enqueued until {}", entry.release_time),
+                None => "enqueued".to_string(),
+            };
+            (finding.clone(), Some(NotFoundCause::Enqueued(finding)), entry)
+        },
+        Ok(false) => ("not enqueued".to_string(), None, None),
+        Err(e) => (format!("check failed: {:?}", e), None, None),
+    }
+}
+
+// outcome of looking the message up on the source chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SourceCheck {
+    // the chain could not be looked up, so nothing is known about the message
+    Unchecked(String),
+    Checked(String, Option<NotFoundCause>),
+}
+
+fn check_source_chain(chain: CooChain, emitter_address: &str, sequence: u64) -> Result<SourceCheck, CooError> {
+    let emitter_bytes = hextobytes(emitter_address)?;
+    // only evm emitters can be looked up through the core bridge
+    if emitter_bytes.len() != 32 || emitter_bytes[..12].iter().any(|b| *b != 0) {
+        return Ok(SourceCheck::Unchecked("emitter is not an evm address".to_string()));
+    }
+    let emitter = EvmAddress::from_slice(&emitter_bytes[12..]);
+    let provider = evm_provider(chain, None)?;
+    let core = evm_contract(chain, EmitterType::CoreBridge, None)?;
+
+    let next_sequence = evm_next_sequence(provider.clone(), core, emitter)?;
+    if sequence >= next_sequence {
+        let finding = format!("sequence {} not published yet, the next sequence of the emitter is {}", sequence, next_sequence);
+        return Ok(SourceCheck::Checked(finding.clone(), Some(NotFoundCause::NotPublished(finding))));
+    }
+    match evm_find_message(provider.clone(), core, emitter, sequence, MESSAGE_LOOKBACK_BLOCKS)? {
+        Some((block, consistency_level)) => {
+            let observable = evm_observable_block(provider, consistency_level)?;
+            if block > observable {
+                let finding = format!("published in block {}, which has not reached consistency level {} (at block {})", block, consistency_level, observable);
+                Ok(SourceCheck::Checked(finding.clone(), Some(NotFoundCause::NotFinalized(finding))))
+            } else {
+                Ok(SourceCheck::Checked(format!("published in block {} and final", block), None))
+            }
+        },
+        None => Ok(SourceCheck::Checked(format!("published more than {} blocks ago", MESSAGE_LOOKBACK_BLOCKS), None)),
+    }
+}
+
+// the governor holding the VAA back explains it whatever the source chain says
+fn likely_cause(governor_cause: Option<NotFoundCause>, source: &SourceCheck) -> NotFoundCause {
+    match (governor_cause, source) {
+        (Some(cause), _) => cause,
+        (None, SourceCheck::Checked(_, Some(cause))) => cause.clone(),
+        // a final message that nobody holds back is only missing signatures
+        (None, SourceCheck::Checked(_, None)) => NotFoundCause::NoQuorum,
+        (None, SourceCheck::Unchecked(_)) => NotFoundCause::Unknown,
+    }
+}
+
+pub fn diagnose_missing_vaa(guardian_url: &Url, chain: CooChain, emitter_address: &str, sequence: u64) -> NotFoundDiagnosis {
+    let emitter_finding = check_emitter(chain, emitter_address);
+    let (governor_finding, governor_cause, enqueued) = check_governor(guardian_url, chain, emitter_address, sequence);
+    let source = check_source_chain(chain, emitter_address, sequence)
+        .unwrap_or_else(|e| SourceCheck::Unchecked(format!("check failed: {:?}", e)));
+    let cause = likely_cause(governor_cause, &source);
+    let source_finding = match source {
+        SourceCheck::Unchecked(finding) | SourceCheck::Checked(finding, _) => finding,
+    };
+    let checks = vec![
+        ("Emitter", emitter_finding),
+        ("Governor", governor_finding),
+        ("Source Chain", source_finding),
+    ];
+    return NotFoundDiagnosis { checks, cause, enqueued };
+}
+
+pub fn pretty_not_found_diagnosis(diagnosis: &NotFoundDiagnosis) -> String {
+    let mut table = Table::new();
+    table.set_header(["VAA Not Found"]);
+    let mut rows: Vec<Row> = diagnosis.checks.iter().map(|(check, finding)| [*check, finding.as_str()].into()).collect();
+    let cause = match &diagnosis.cause {
+        NotFoundCause::Enqueued(f) => format!("held by the governor: {}", f),
+        NotFoundCause::NotPublished(f) => format!("wrong sequence: {}", f),
+        NotFoundCause::NotFinalized(f) => format!("not final yet: {}", f),
+        NotFoundCause::NoQuorum => "the message is final and not governed, the guardians have not reached quorum".to_string(),
+        NotFoundCause::Unknown => "unknown, the source chain could not be checked".to_string(),
+    };
+    rows.push(["Likely Cause", &cause].into());
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_emitter() {
+        let relayer = "27428DD2d3DD32A4D7f7C497eAaa23130d894911";
+        assert_eq!(check_emitter(CooChain::Inner(Chain::Ethereum), relayer), format!("relayer emitter of {}", Chain::Ethereum));
+        assert_eq!(check_emitter(CooChain::Inner(Chain::Avalanche), &relayer.to_lowercase()), format!("relayer emitter of {}", Chain::Avalanche));
+        // the relayer is deployed at the same address on chains that are not listed
+        assert_eq!(
+            check_emitter(CooChain::Inner(Chain::Bsc), relayer),
+            format!("relayer emitter of {}, {}, not known on {}", Chain::Ethereum, Chain::Avalanche, Chain::Bsc),
+        );
+        assert_eq!(check_emitter(CooChain::Inner(Chain::Ethereum), &"11".repeat(20)), "not a known emitter");
+    }
+
+    #[test]
+    fn test_likely_cause() {
+        let enqueued = NotFoundCause::Enqueued("enqueued".to_string());
+        let not_final = NotFoundCause::NotFinalized("not final".to_string());
+        let unchecked = SourceCheck::Unchecked("emitter is not an evm address".to_string());
+        assert_eq!(likely_cause(Some(enqueued.clone()), &SourceCheck::Checked(String::new(), Some(not_final.clone()))), enqueued);
+        assert_eq!(likely_cause(Some(enqueued.clone()), &unchecked), enqueued);
+        assert_eq!(likely_cause(None, &SourceCheck::Checked(String::new(), Some(not_final.clone()))), not_final);
+        assert_eq!(likely_cause(None, &SourceCheck::Checked(String::new(), None)), NotFoundCause::NoQuorum);
+        // nothing is known about a message on a chain that was not checked
+        assert_eq!(likely_cause(None, &unchecked), NotFoundCause::Unknown);
+    }
+}
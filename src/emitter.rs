@@ -0,0 +1,99 @@
+use bech32::FromBase32;
+use comfy_table::{Table, Row};
+use wormhole_sdk::{Address, Chain};
+
+use crate::common::*;
+use crate::solana::{base58topubkey, emitter_seeds, find_program_address};
+
+// how the emitter address of a program is derived on each chain family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmitterDerivation {
+    // pda of the "emitter" seed under the program
+    SolanaPda,
+    // u128 id of the emitter capability handed out by the core bridge
+    AptosEmitterCapability,
+    // object id of the emitter cap
+    SuiEmitterCap,
+    // canonical (bech32 decoded) contract address
+    CosmwasmContract,
+    // the contract address itself
+    Evm,
+}
+
+pub fn emitter_derivation(chain: Chain) -> EmitterDerivation {
+    match u16::from(chain) {
+        1 | 26 => EmitterDerivation::SolanaPda,
+        22 => EmitterDerivation::AptosEmitterCapability,
+        21 => EmitterDerivation::SuiEmitterCap,
+        3 | 18 | 19 | 20 | 28 | 32 | 3104 | 4000..=4999 => EmitterDerivation::CosmwasmContract,
+        _ => EmitterDerivation::Evm,
+    }
+}
+
+fn cosmwasmtoaddress(program: &str) -> Result<Address, CooError> {
+    let (_, data, _) = bech32::decode(program)
+        .map_err(|e| CooError::ParseError(format!("invalid bech32 contract address {}: {}", program, e)))?;
+    let bytes = Vec::<u8>::from_base32(&data)
+        .map_err(|e| CooError::ParseError(format!("invalid bech32 contract address {}: {}", program, e)))?;
+    if bytes.len() > 32 {
+        return Err(CooError::ParseError(format!("contract address is longer than 32 bytes: {}", program)));
+    }
+    // 20 byte addresses (e.g. terra classic) are left padded like evm addresses
+    let mut address = [0u8; 32];
+    address[32 - bytes.len()..].copy_from_slice(&bytes);
+    return Ok(Address(address));
+}
+
+pub fn derive_emitter_address(chain: Chain, program: &str) -> Result<Address, CooError> {
+    match emitter_derivation(chain) {
+        EmitterDerivation::SolanaPda => {
+            let program_id = base58topubkey(program)?;
+            let seeds = emitter_seeds();
+            let seeds: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+            let (address, _) = find_program_address(&seeds, &program_id)?;
+            Ok(Address(address))
+        },
+        EmitterDerivation::AptosEmitterCapability => {
+            // the capability id is a decimal counter, e.g. 1 for the token bridge
+            match program.parse::<u128>() {
+                Ok(id) => {
+                    let mut address = [0u8; 32];
+                    address[16..].copy_from_slice(&id.to_be_bytes());
+                    Ok(Address(address))
+                },
+                Err(_) => hextoaddress(program),
+            }
+        },
+        EmitterDerivation::CosmwasmContract => cosmwasmtoaddress(program),
+        EmitterDerivation::SuiEmitterCap |
+        EmitterDerivation::Evm => hextoaddress(program),
+    }
+}
+
+pub fn pretty_emitter_address(chain: Chain, program: &str, derivation: EmitterDerivation, emitter_address: &Address) -> String {
+    let mut table = Table::new();
+    table.set_header(["Emitter Information"]);
+    let rows: Vec<Row> = vec![
+        ["Chain", &format!("{} ({})", u16::from(chain), chain)].into(),
+        ["Program", program].into(),
+        ["Derivation", &format!("{:?}", derivation)].into(),
+        ["Emitter Address", &hex::encode(emitter_address.0)].into(),
+    ];
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_emitter_address() {
+        let solana = derive_emitter_address(Chain::Solana, crate::solana::TOKEN_BRIDGE_PROGRAM).unwrap();
+        assert_eq!(hex::encode(solana.0), "ec7372995d5cc8732397fb0ad35c0121e0eaa90d26f828a534cab54391b3a4f5");
+        let aptos = derive_emitter_address(Chain::from(22u16), "1").unwrap();
+        assert_eq!(hex::encode(aptos.0), format!("{:0>64}", "1"));
+        let wormchain = derive_emitter_address(Chain::from(3104u16), "wormhole14ejqjyq8um4p3xfqj74yld5waqljf88fz25yxnma0cngspxe3les00fpjx").unwrap();
+        assert_eq!(hex::encode(wormchain.0), crate::gateway::GATEWAY_CONTRACT);
+    }
+}
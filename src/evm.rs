@@ -0,0 +1,507 @@
+use std::future::Future;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use comfy_table::{Table, Row};
+use ethers::abi::{decode, encode, ParamType, Token};
+use ethers::contract::abigen;
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address as EvmAddress, BlockNumber, Bytes, H256, TransactionReceipt, TransactionRequest, U256};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::{id, keccak256};
+use wormhole_sdk::{Address, Chain, Vaa};
+use serde_wormhole::RawMessage;
+
+use crate::common::*;
+use crate::governance::{GovernanceModule, decode_governance_header, is_governance_vaa};
+
+abigen!(
+    TokenBridge,
+    r#"[
+        function isTransferCompleted(bytes32 hash) external view returns (bool)
+        function wrappedAsset(uint16 tokenChainId, bytes32 tokenAddress) external view returns (address)
+        function wormhole() external view returns (address)
+        event TransferRedeemed(uint16 indexed emitterChainId, bytes32 indexed emitterAddress, uint64 indexed sequence)
+    ]"#
+);
+
+abigen!(
+    CoreBridge,
+    r#"[
+        function messageFee() external view returns (uint256)
+        function getCurrentGuardianSetIndex() external view returns (uint32)
+        function governanceChainId() external view returns (uint16)
+        function governanceContract() external view returns (bytes32)
+        function chainId() external view returns (uint16)
+        function nextSequence(address emitter) external view returns (uint64)
+        event LogMessagePublished(address indexed sender, uint64 sequence, uint32 nonce, bytes payload, uint8 consistencyLevel)
+    ]"#
+);
+
+abigen!(
+    NftBridge,
+    r#"[
+        function isTransferCompleted(bytes32 hash) external view returns (bool)
+    ]"#
+);
+
+// ethers is async only, everything else in here is blocking
+pub fn block_on<F: Future>(future: F) -> Result<F::Output, CooError> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    return Ok(runtime.block_on(future));
+}
+
+pub fn evm_provider(chain: CooChain, rpc_url: Option<&str>) -> Result<Arc<Provider<Http>>, CooError> {
+    let rpc_url = match rpc_url {
+        Some(url) => url,
+        None => *RPC_ENDPOINTS.get(&chain)
+            .ok_or_else(|| CooError::ParseError(format!("no rpc endpoint known for chain {}, use --rpc-url", u16::from(chain))))?,
+    };
+    let provider = Provider::<Http>::try_from(rpc_url)?;
+    return Ok(Arc::new(provider));
+}
+
+// the known contract of a chain, or the given override (e.g. a local anvil deployment)
+pub fn evm_contract(chain: CooChain, emitter: EmitterType, contract: Option<&str>) -> Result<EvmAddress, CooError> {
+    let contract = match contract {
+        Some(c) => c,
+        None => *EMITTERS.get(&(chain, emitter))
+            .ok_or_else(|| CooError::ParseError(format!("no {} contract known for chain {}, use --contract", emitter, u16::from(chain))))?,
+    };
+    return Ok(EvmAddress::from_slice(&hextoevmaddress(contract)?));
+}
+
+pub fn hextoevmaddress(s: &str) -> Result<[u8; 20], CooError> {
+    let bytes = hextobytes(s)?;
+    // wormhole addresses of evm contracts are 0 left-padded to 32 bytes
+    let bytes = match bytes.len() {
+        20 => &bytes[..],
+        32 if bytes[..12].iter().all(|b| *b == 0) => &bytes[12..],
+        _ => return Err(CooError::ParseError(format!("not an evm address: {}", s))),
+    };
+    return Ok(bytes.try_into().unwrap());
+}
+
+#[derive(Debug, Clone)]
+pub struct RedemptionStatus {
+    pub chain: Chain,
+    pub contract: String,
+    pub redeemed: bool,
+    pub transaction: Option<String>,
+}
+
+pub fn evm_transfer_status(provider: Arc<Provider<Http>>, chain: Chain, emitter: EmitterType, contract: EvmAddress, digest: [u8; 32], vaa_key: (u16, Address, u64), from_block: u64) -> Result<RedemptionStatus, CooError> {
+    let (redeemed, transaction) = match emitter {
+        EmitterType::NftBridge => {
+            let bridge = NftBridge::new(contract, provider);
+            (block_on(bridge.is_transfer_completed(digest).call())??, None)
+        },
+        _ => {
+            let bridge = TokenBridge::new(contract, provider);
+            let redeemed = block_on(bridge.is_transfer_completed(digest).call())??;
+            // older token bridge deployments don't emit TransferRedeemed, and some rpcs limit the log range,
+            // so not finding the redemption is not an error
+            let transaction = if redeemed {
+                let (emitter_chain, emitter_address, sequence) = vaa_key;
+                let filter = bridge.transfer_redeemed_filter()
+                    .from_block(from_block)
+                    .topic1(U256::from(emitter_chain))
+                    .topic2(H256::from(emitter_address.0))
+                    .topic3(U256::from(sequence));
+                match block_on(filter.query_with_meta())? {
+                    Ok(logs) => logs.first().map(|(_, meta)| format!("{:?}", meta.transaction_hash)),
+                    Err(_) => None,
+                }
+            } else {
+                None
+            };
+            (redeemed, transaction)
+        },
+    };
+    return Ok(RedemptionStatus { chain, contract: format!("{:?}", contract), redeemed, transaction });
+}
+
+pub fn pretty_redemption_status(status: &RedemptionStatus) -> String {
+    let mut table = Table::new();
+    table.set_header(["Redemption Status"]);
+    let rows: Vec<Row> = vec![
+        ["Target Chain", &format!("{} ({})", u16::from(status.chain), status.chain)].into(),
+        ["Contract", &status.contract].into(),
+        ["Redeemed", if status.redeemed { "yes" } else { "no" }].into(),
+        ["Redemption Transaction", status.transaction.as_deref().unwrap_or("unknown")].into(),
+    ];
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+// the contract call that submits a VAA on an evm chain
+#[derive(Debug, Clone)]
+pub struct RedeemCall {
+    pub bridge: EmitterType,
+    pub function: &'static str,
+    pub calldata: Vec<u8>,
+}
+
+// signature is the canonical solidity signature, e.g. transferTokens(address,uint256,uint16,bytes32,uint256,uint32)
+pub fn encode_call(signature: &str, args: &[Token]) -> Vec<u8> {
+    let mut calldata = id(signature).to_vec();
+    calldata.extend_from_slice(&encode(args));
+    return calldata;
+}
+
+// every VAA entrypoint takes the encoded VAA as its only argument
+fn encode_vaa_call(function: &str, vaa_bytes: &[u8]) -> Vec<u8> {
+    encode_call(&format!("{}(bytes)", function), &[Token::Bytes(vaa_bytes.to_vec())])
+}
+
+pub fn redeem_call(vaa: &Vaa<&RawMessage>, payload_type: PayloadType, vaa_bytes: &[u8], unwrap_native: bool, update_wrapped: bool) -> Result<RedeemCall, CooError> {
+    let (bridge, function) = if is_governance_vaa(vaa) {
+        let (module, action, _) = decode_governance_header(vaa.payload)?;
+        match (module, action) {
+            (GovernanceModule::Core, 1) => (EmitterType::CoreBridge, "submitContractUpgrade"),
+            (GovernanceModule::Core, 2) => (EmitterType::CoreBridge, "submitNewGuardianSet"),
+            (GovernanceModule::Core, 3) => (EmitterType::CoreBridge, "submitSetMessageFee"),
+            (GovernanceModule::Core, 4) => (EmitterType::CoreBridge, "submitTransferFees"),
+            (GovernanceModule::Core, 5) => (EmitterType::CoreBridge, "submitRecoverChainId"),
+            (GovernanceModule::TokenBridge, 1) => (EmitterType::TokenBridge, "registerChain"),
+            (GovernanceModule::TokenBridge, 2) => (EmitterType::TokenBridge, "upgrade"),
+            (GovernanceModule::TokenBridge, 3) => (EmitterType::TokenBridge, "submitRecoverChainId"),
+            (GovernanceModule::NftBridge, 1) => (EmitterType::NftBridge, "registerChain"),
+            (GovernanceModule::NftBridge, 2) => (EmitterType::NftBridge, "upgrade"),
+            (module, action) => return Err(CooError::ParseError(format!("unsupported {} governance action: {}", module.name(), action))),
+        }
+    } else {
+        match payload_type {
+            PayloadType::WormholeTokenTransfer if unwrap_native => (EmitterType::TokenBridge, "completeTransferAndUnwrapETH"),
+            PayloadType::WormholeTokenTransfer => (EmitterType::TokenBridge, "completeTransfer"),
+            PayloadType::WormholeTokenTransferPayload if unwrap_native => (EmitterType::TokenBridge, "completeTransferAndUnwrapETHWithPayload"),
+            PayloadType::WormholeTokenTransferPayload => (EmitterType::TokenBridge, "completeTransferWithPayload"),
+            PayloadType::WormholeAssetMeta if update_wrapped => (EmitterType::TokenBridge, "updateWrapped"),
+            PayloadType::WormholeAssetMeta => (EmitterType::TokenBridge, "createWrapped"),
+            PayloadType::WormholeNftTransfer => (EmitterType::NftBridge, "completeTransfer"),
+            v => return Err(CooError::ParseError(format!("no evm entrypoint for {:?} VAAs", v))),
+        }
+    };
+    return Ok(RedeemCall { bridge, function, calldata: encode_vaa_call(function, vaa_bytes) });
+}
+
+// any VAA can be verified by the core bridge, whatever its payload
+pub fn verify_call(vaa_bytes: &[u8]) -> RedeemCall {
+    let function = "parseAndVerifyVM";
+    RedeemCall { bridge: EmitterType::CoreBridge, function, calldata: encode_vaa_call(function, vaa_bytes) }
+}
+
+pub fn pretty_redeem_call(chain: Chain, target: &EvmAddress, call: &RedeemCall) -> String {
+    let mut table = Table::new();
+    table.set_header(["Contract Call"]);
+    let rows: Vec<Row> = vec![
+        ["Chain", &format!("{} ({})", u16::from(chain), chain)].into(),
+        ["Target Contract", &format!("{:?}", target)].into(),
+        ["Function", &format!("{}(bytes)", call.function)].into(),
+        ["Calldata", &format!("0x{}", hex::encode(&call.calldata))].into(),
+    ];
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+// an asset meta VAA updates the wrapped token if it has already been created
+pub fn evm_wrapped_asset(provider: Arc<Provider<Http>>, contract: EvmAddress, token_chain: u16, token_address: &Address) -> Result<Option<EvmAddress>, CooError> {
+    let bridge = TokenBridge::new(contract, provider);
+    let wrapped = block_on(bridge.wrapped_asset(token_chain, token_address.0).call())??;
+    if wrapped == EvmAddress::zero() {
+        return Ok(None);
+    }
+    return Ok(Some(wrapped));
+}
+
+// the core bridge that a token bridge publishes through, and the fee it charges per message
+pub fn evm_message_fee(provider: Arc<Provider<Http>>, token_bridge: EvmAddress) -> Result<(EvmAddress, U256), CooError> {
+    let bridge = TokenBridge::new(token_bridge, provider.clone());
+    let core = block_on(bridge.wormhole().call())??;
+    return Ok((core, evm_core_message_fee(provider, core)?));
+}
+
+pub fn evm_core_message_fee(provider: Arc<Provider<Http>>, core: EvmAddress) -> Result<U256, CooError> {
+    let core_bridge = CoreBridge::new(core, provider);
+    return Ok(block_on(core_bridge.message_fee().call())??);
+}
+
+#[derive(Debug, Clone)]
+pub struct CoreBridgeState {
+    pub chain_id: u16,
+    pub guardian_set_index: u32,
+    pub guardians: Vec<[u8; 20]>,
+    pub guardian_set_expiration: u32,
+    pub message_fee: U256,
+    pub governance_chain: u16,
+    pub governance_contract: [u8; 32],
+}
+
+// the keys and expiration time of a guardian set, the core bridge keeps every set it has been upgraded through
+pub fn evm_guardian_set(provider: Arc<Provider<Http>>, core: EvmAddress, guardian_set_index: u32) -> Result<(Vec<[u8; 20]>, u32), CooError> {
+    // getGuardianSet returns a struct, which is decoded by hand
+    let calldata = encode_call("getGuardianSet(uint32)", &[Token::Uint(guardian_set_index.into())]);
+    let tx: TypedTransaction = TransactionRequest::new().to(core).data(calldata).into();
+    let output = block_on(provider.call(&tx, None))??;
+    let guardian_set_type = ParamType::Tuple(vec![ParamType::Array(Box::new(ParamType::Address)), ParamType::Uint(32)]);
+    let tokens = decode(&[guardian_set_type], &output)
+        .map_err(|e| CooError::ParseError(format!("invalid guardian set: {}", e)))?;
+    let (guardians, guardian_set_expiration) = match tokens.into_iter().next() {
+        Some(Token::Tuple(fields)) => match (fields.get(0), fields.get(1)) {
+            (Some(Token::Array(keys)), Some(Token::Uint(expiration))) => {
+                let keys = keys.iter().filter_map(|k| match k {
+                    Token::Address(a) => Some(a.0),
+                    _ => None,
+                }).collect();
+                (keys, expiration.as_u32())
+            },
+            _ => return Err(CooError::ParseError("invalid guardian set".to_string())),
+        },
+        _ => return Err(CooError::ParseError("invalid guardian set".to_string())),
+    };
+    return Ok((guardians, guardian_set_expiration));
+}
+
+pub fn evm_core_state(provider: Arc<Provider<Http>>, core: EvmAddress) -> Result<CoreBridgeState, CooError> {
+    let core_bridge = CoreBridge::new(core, provider.clone());
+    let guardian_set_index = block_on(core_bridge.get_current_guardian_set_index().call())??;
+    let (guardians, guardian_set_expiration) = evm_guardian_set(provider, core, guardian_set_index)?;
+    return Ok(CoreBridgeState {
+        chain_id: block_on(core_bridge.chain_id().call())??,
+        guardian_set_index,
+        guardians,
+        guardian_set_expiration,
+        message_fee: block_on(core_bridge.message_fee().call())??,
+        governance_chain: block_on(core_bridge.governance_chain_id().call())??,
+        governance_contract: block_on(core_bridge.governance_contract().call())??,
+    });
+}
+
+pub fn evm_next_sequence(provider: Arc<Provider<Http>>, core: EvmAddress, emitter: EvmAddress) -> Result<u64, CooError> {
+    let core_bridge = CoreBridge::new(core, provider);
+    return Ok(block_on(core_bridge.next_sequence(emitter).call())??);
+}
+
+// where a message was published, as (block number, consistency level), searching the last `lookback` blocks
+pub fn evm_find_message(provider: Arc<Provider<Http>>, core: EvmAddress, emitter: EvmAddress, sequence: u64, lookback: u64) -> Result<Option<(u64, u8)>, CooError> {
+    let latest = block_on(provider.get_block_number())??.as_u64();
+    let core_bridge = CoreBridge::new(core, provider);
+    // the sequence is not indexed, so we filter on the emitter and look through its messages
+    let filter = core_bridge.log_message_published_filter()
+        .from_block(latest.saturating_sub(lookback))
+        .topic1(H256::from(emitter));
+    let logs = block_on(filter.query_with_meta())??;
+    let message = logs.iter()
+        .find(|(log, _)| log.sequence == sequence)
+        .map(|(log, meta)| (meta.block_number.as_u64(), log.consistency_level));
+    return Ok(message);
+}
+
+// the block a message with this consistency level has to reach before the guardians observe it
+pub fn evm_observable_block(provider: Arc<Provider<Http>>, consistency_level: u8) -> Result<u64, CooError> {
+    let block = match consistency_level {
+        200 => BlockNumber::Latest,
+        201 => BlockNumber::Safe,
+        _ => BlockNumber::Finalized,
+    };
+    let block = block_on(provider.get_block(block))??
+        .and_then(|b| b.number)
+        .ok_or_else(|| CooError::ParseError(format!("rpc did not return the {:?} block", block)))?;
+    return Ok(block.as_u64());
+}
+
+pub fn publish_message_calldata(nonce: u32, payload: &[u8], consistency_level: u8) -> Vec<u8> {
+    encode_call(
+        "publishMessage(uint32,bytes,uint8)",
+        &[Token::Uint(nonce.into()), Token::Bytes(payload.to_vec()), Token::Uint(consistency_level.into())],
+    )
+}
+
+// sequence of the message that sender published through the core bridge in this transaction
+pub fn parse_log_message_published(receipt: &TransactionReceipt, core: EvmAddress, sender: EvmAddress) -> Result<u64, CooError> {
+    let topic = H256::from(keccak256("LogMessagePublished(address,uint64,uint32,bytes,uint8)"));
+    let log = receipt.logs.iter()
+        .find(|log| log.address == core && log.topics.first() == Some(&topic) && log.topics.get(1) == Some(&H256::from(sender)))
+        .ok_or_else(|| CooError::ParseError(format!("no LogMessagePublished from {:?} in transaction {:?}", sender, receipt.transaction_hash)))?;
+    let tokens = decode(&[ParamType::Uint(64), ParamType::Uint(32), ParamType::Bytes, ParamType::Uint(8)], &log.data)
+        .map_err(|e| CooError::ParseError(format!("invalid LogMessagePublished data: {}", e)))?;
+    match tokens.first() {
+        Some(Token::Uint(sequence)) => Ok(sequence.as_u64()),
+        _ => Err(CooError::ParseError("LogMessagePublished without a sequence".to_string())),
+    }
+}
+
+pub fn evm_wallet(private_key: Option<&str>, keystore: Option<&Path>, password: Option<&str>) -> Result<LocalWallet, CooError> {
+    match (private_key, keystore) {
+        (Some(key), _) => Ok(LocalWallet::from_str(&hex::encode(hextobytes(key)?))?),
+        (None, Some(keystore)) => {
+            let password = password.ok_or_else(|| CooError::ParseError("a keystore needs a password".to_string()))?;
+            Ok(LocalWallet::decrypt_keystore(keystore, password)?)
+        },
+        (None, None) => Err(CooError::ParseError("either a private key or a keystore is needed to sign".to_string())),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum EvmSendResult {
+    // eth_call output of a dry run
+    DryRun { gas: U256, output: Bytes },
+    Receipt(TransactionReceipt),
+}
+
+async fn send_transaction(provider: Arc<Provider<Http>>, wallet: LocalWallet, to: EvmAddress, calldata: Vec<u8>, value: U256, dry_run: bool) -> Result<EvmSendResult, CooError> {
+    let chain_id = provider.get_chainid().await?;
+    let client = SignerMiddleware::new(provider, wallet.with_chain_id(chain_id.as_u64()));
+    let tx = TransactionRequest::new()
+        .from(client.address())
+        .to(to)
+        .value(value)
+        .data(calldata);
+    let mut tx: TypedTransaction = tx.into();
+    if dry_run {
+        // eth_call first, a failing gas estimate would hide the revert reason
+        let output = client.call(&tx, None).await.map_err(|e| CooError::ContractError(e.to_string()))?;
+        let gas = client.estimate_gas(&tx, None).await.map_err(|e| CooError::ContractError(e.to_string()))?;
+        return Ok(EvmSendResult::DryRun { gas, output });
+    }
+    let gas = client.estimate_gas(&tx, None).await.map_err(|e| CooError::ContractError(e.to_string()))?;
+    tx.set_gas(gas);
+    let pending = client.send_transaction(tx, None).await.map_err(|e| CooError::ContractError(e.to_string()))?;
+    let receipt = pending.await?
+        .ok_or_else(|| CooError::ContractError("transaction was dropped from the mempool".to_string()))?;
+    return Ok(EvmSendResult::Receipt(receipt));
+}
+
+pub fn evm_send(provider: Arc<Provider<Http>>, wallet: LocalWallet, to: EvmAddress, calldata: Vec<u8>, value: U256, dry_run: bool) -> Result<EvmSendResult, CooError> {
+    block_on(send_transaction(provider, wallet, to, calldata, value, dry_run))?
+}
+
+pub fn evm_receipt(result: EvmSendResult) -> Result<TransactionReceipt, CooError> {
+    match result {
+        EvmSendResult::Receipt(receipt) if receipt.status.map(|s| s.as_u64()) == Some(0) => {
+            Err(CooError::ContractError(format!("transaction {:?} reverted", receipt.transaction_hash)))
+        },
+        EvmSendResult::Receipt(receipt) => Ok(receipt),
+        EvmSendResult::DryRun { .. } => Err(CooError::ContractError("a dry run has no receipt".to_string())),
+    }
+}
+
+pub fn pretty_send_result(result: &EvmSendResult) -> String {
+    let mut table = Table::new();
+    let rows: Vec<Row> = match result {
+        EvmSendResult::DryRun { gas, output } => {
+            table.set_header(["Dry Run"]);
+            vec![
+                ["Estimated Gas", &gas.to_string()].into(),
+                ["Return Data", &hex::encode(output)].into(),
+            ]
+        },
+        EvmSendResult::Receipt(receipt) => {
+            table.set_header(["Transaction Receipt"]);
+            let status = match receipt.status.map(|s| s.as_u64()) {
+                Some(1) => "success",
+                Some(_) => "reverted",
+                None => "unknown",
+            };
+            vec![
+                ["Transaction Hash", &format!("{:?}", receipt.transaction_hash)].into(),
+                ["Block Number", &receipt.block_number.map(|b| b.to_string()).unwrap_or_default()].into(),
+                ["Status", status].into(),
+                ["Gas Used", &receipt.gas_used.map(|g| g.to_string()).unwrap_or_default()].into(),
+                ["Logs", &receipt.logs.len().to_string()].into(),
+            ]
+        },
+    };
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vaa::{build_vaa, test_vaa_header, CooVaaHeader};
+
+    #[test]
+    fn test_encode_vaa_call() {
+        let calldata = encode_vaa_call("completeTransfer", &[0xaa, 0xbb]);
+        // selector, offset of the bytes, length and the right padded data
+        assert_eq!(hex::encode(&calldata[..4]), "c6878519");
+        assert_eq!(U256::from_big_endian(&calldata[4..36]), U256::from(32));
+        assert_eq!(U256::from_big_endian(&calldata[36..68]), U256::from(2));
+        assert_eq!(&calldata[68..70], &[0xaa, 0xbb]);
+        assert_eq!(calldata.len(), 100);
+    }
+
+    #[test]
+    fn test_publish_message_calldata() {
+        let calldata = publish_message_calldata(7, &[0xaa, 0xbb, 0xcc], 200);
+        assert_eq!(hex::encode(&calldata[..4]), "b19a437e");
+        // nonce, offset of the payload, consistency level, then the payload itself
+        assert_eq!(calldata.len(), 4 + 5 * 32);
+        assert_eq!(U256::from_big_endian(&calldata[4..36]), U256::from(7));
+        assert_eq!(U256::from_big_endian(&calldata[36..68]), U256::from(96));
+        assert_eq!(U256::from_big_endian(&calldata[68..100]), U256::from(200));
+        assert_eq!(U256::from_big_endian(&calldata[100..132]), U256::from(3));
+        assert_eq!(&calldata[132..135], &[0xaa, 0xbb, 0xcc]);
+        assert!(calldata[135..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn test_parse_log_message_published() {
+        let core = EvmAddress::from([0x98; 20]);
+        let sender = EvmAddress::from([0x3e; 20]);
+        let message_log = |address: EvmAddress, sender: EvmAddress, sequence: u64| ethers::types::Log {
+            address,
+            topics: vec![H256::from(keccak256("LogMessagePublished(address,uint64,uint32,bytes,uint8)")), H256::from(sender)],
+            data: encode(&[Token::Uint(sequence.into()), Token::Uint(7.into()), Token::Bytes(vec![1, 2, 3]), Token::Uint(15.into())]).into(),
+            ..Default::default()
+        };
+        let mut receipt = TransactionReceipt::default();
+        // a token approval and a message of another emitter come before the transfer
+        receipt.logs.push(ethers::types::Log { address: EvmAddress::from([0x22; 20]), topics: vec![H256::from(keccak256("Approval(address,address,uint256)"))], ..Default::default() });
+        receipt.logs.push(message_log(core, EvmAddress::from([0x44; 20]), 1));
+        receipt.logs.push(message_log(core, sender, 5321));
+        assert_eq!(parse_log_message_published(&receipt, core, sender).unwrap(), 5321);
+        // messages of another core bridge deployment don't count
+        assert!(parse_log_message_published(&receipt, EvmAddress::from([0x99; 20]), sender).is_err());
+    }
+
+    fn test_vaa<'a>(emitter_chain: Chain, emitter_address: Address, payload: &'a [u8]) -> Vaa<&'a RawMessage> {
+        build_vaa(&CooVaaHeader { chain: emitter_chain.into(), emitter_address, ..test_vaa_header() }, payload)
+    }
+
+    fn redeem_target(vaa: &Vaa<&RawMessage>, payload_type: PayloadType, unwrap_native: bool, update_wrapped: bool) -> (EmitterType, &'static str) {
+        let call = redeem_call(vaa, payload_type, &[], unwrap_native, update_wrapped).unwrap();
+        (call.bridge, call.function)
+    }
+
+    #[test]
+    fn test_redeem_call() {
+        use wormhole_sdk::Amount;
+        use crate::governance::{GOVERNANCE_CHAIN, GOVERNANCE_EMITTER, register_chain_payload, set_message_fee_payload};
+
+        let transfer = test_vaa(Chain::Ethereum, Address([1u8; 32]), &[1]);
+        assert_eq!(redeem_target(&transfer, PayloadType::WormholeTokenTransfer, false, false), (EmitterType::TokenBridge, "completeTransfer"));
+        assert_eq!(redeem_target(&transfer, PayloadType::WormholeTokenTransfer, true, false), (EmitterType::TokenBridge, "completeTransferAndUnwrapETH"));
+        assert_eq!(redeem_target(&transfer, PayloadType::WormholeTokenTransferPayload, false, false), (EmitterType::TokenBridge, "completeTransferWithPayload"));
+        assert_eq!(redeem_target(&transfer, PayloadType::WormholeTokenTransferPayload, true, false), (EmitterType::TokenBridge, "completeTransferAndUnwrapETHWithPayload"));
+        assert_eq!(redeem_target(&transfer, PayloadType::WormholeAssetMeta, false, false), (EmitterType::TokenBridge, "createWrapped"));
+        assert_eq!(redeem_target(&transfer, PayloadType::WormholeAssetMeta, false, true), (EmitterType::TokenBridge, "updateWrapped"));
+        assert_eq!(redeem_target(&transfer, PayloadType::WormholeNftTransfer, false, false), (EmitterType::NftBridge, "completeTransfer"));
+        assert!(redeem_call(&transfer, PayloadType::RawBytes, &[], false, false).is_err());
+
+        // governance VAAs are routed by module and action, whatever the payload type
+        let fee = set_message_fee_payload(CooChain::from(2u16), &Amount([0u8; 32])).unwrap();
+        let governance = test_vaa(GOVERNANCE_CHAIN, GOVERNANCE_EMITTER, &fee);
+        assert_eq!(redeem_target(&governance, PayloadType::RawBytes, false, false), (EmitterType::CoreBridge, "submitSetMessageFee"));
+        let register = register_chain_payload(GovernanceModule::TokenBridge, CooChain::from(6u16), &Address([2u8; 32])).unwrap();
+        let governance = test_vaa(GOVERNANCE_CHAIN, GOVERNANCE_EMITTER, &register);
+        assert_eq!(redeem_target(&governance, PayloadType::RawBytes, false, false), (EmitterType::TokenBridge, "registerChain"));
+        let register = register_chain_payload(GovernanceModule::NftBridge, CooChain::from(6u16), &Address([2u8; 32])).unwrap();
+        let governance = test_vaa(GOVERNANCE_CHAIN, GOVERNANCE_EMITTER, &register);
+        assert_eq!(redeem_target(&governance, PayloadType::RawBytes, false, false), (EmitterType::NftBridge, "registerChain"));
+    }
+}
@@ -0,0 +1,159 @@
+use bech32::{ToBase32, Variant};
+use comfy_table::{Table, Row};
+use serde::Deserialize;
+use wormhole_sdk::{Address, Chain};
+
+use crate::common::*;
+
+// wormhole gateway lives on wormchain as the ibc translator contract
+pub const GATEWAY_CHAIN: u16 = 3104;
+pub const GATEWAY_CONTRACT: &str = "ae64091007e6ea18992097aa4fb68ee83f249ce912a8434f7d7e268804d98ff3";
+const WORMCHAIN_HRP: &str = "wormhole";
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GatewayPayload {
+    Transfer { chain: u16, recipient: String, fee: String, nonce: u32 },
+    TransferWithPayload { chain: u16, contract: String, payload: Vec<u8>, nonce: u32 },
+}
+
+// addresses are base64 encoded bech32 strings
+#[derive(Debug, Deserialize)]
+struct GatewayTransferFields {
+    chain: u16,
+    recipient: String,
+    fee: String,
+    nonce: u32,
+}
+
+// the payload is base64 encoded and handed to the contract on the destination chain
+#[derive(Debug, Deserialize)]
+struct GatewayTransferWithPayloadFields {
+    chain: u16,
+    contract: String,
+    payload: String,
+    nonce: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum GatewayMessage {
+    GatewayTransfer(GatewayTransferFields),
+    #[serde(alias = "gateway_ibc_hooks")]
+    GatewayTransferWithPayload(GatewayTransferWithPayloadFields),
+}
+
+pub fn gateway_chain_name(chain: u16) -> &'static str {
+    match chain {
+        20 => "osmosis",
+        3104 => "wormchain",
+        4000 => "cosmoshub",
+        4001 => "evmos",
+        4002 => "kujira",
+        4003 => "neutron",
+        4004 => "celestia",
+        4005 => "stargaze",
+        4006 => "seda",
+        4007 => "dymension",
+        4008 => "provenance",
+        4009 => "noble",
+        _ => "unknown",
+    }
+}
+
+pub fn is_gateway_transfer(recipient_chain: Chain, recipient: &Address) -> bool {
+    u16::from(recipient_chain) == GATEWAY_CHAIN && hex::encode(recipient.0) == GATEWAY_CONTRACT
+}
+
+pub fn wormchaintobech32(address: &Address) -> String {
+    bech32::encode(WORMCHAIN_HRP, address.0.to_base32(), Variant::Bech32).unwrap()
+}
+
+fn base64tobech32(field: &str, s: &str) -> Result<String, CooError> {
+    String::from_utf8(base64tobytes(s)?)
+        .map_err(|e| CooError::ParseError(format!("gateway {} is not a bech32 string: {}", field, e)))
+}
+
+pub fn decode_gateway_payload(payload: &[u8]) -> Result<GatewayPayload, CooError> {
+    let message: GatewayMessage = serde_json::from_slice(payload)?;
+    let gateway_payload = match message {
+        GatewayMessage::GatewayTransfer(fields) => GatewayPayload::Transfer {
+            chain: fields.chain,
+            recipient: base64tobech32("recipient", &fields.recipient)?,
+            fee: fields.fee,
+            nonce: fields.nonce,
+        },
+        GatewayMessage::GatewayTransferWithPayload(fields) => GatewayPayload::TransferWithPayload {
+            chain: fields.chain,
+            contract: base64tobech32("contract", &fields.contract)?,
+            payload: base64tobytes(&fields.payload)?,
+            nonce: fields.nonce,
+        },
+    };
+    return Ok(gateway_payload);
+}
+
+pub fn pretty_gateway_payload(gateway_payload: &GatewayPayload) -> String {
+    let mut table = Table::new();
+    table.set_header(["Wormhole Gateway Payload Information"]);
+    let rows: Vec<Row> = match gateway_payload {
+        GatewayPayload::Transfer { chain, recipient, fee, nonce } => vec![
+            ["Payload Type", "gateway_transfer"].into(),
+            ["Destination Chain", &format!("{} ({})", chain, gateway_chain_name(*chain))].into(),
+            ["Recipient", recipient].into(),
+            ["Fee", fee].into(),
+            ["Nonce", &nonce.to_string()].into(),
+        ],
+        GatewayPayload::TransferWithPayload { chain, contract, payload, nonce } => {
+            // payloads are usually json for the receiving contract
+            let payload = String::from_utf8(payload.clone()).unwrap_or_else(|_| hex::encode(payload));
+            vec![
+                ["Payload Type", "gateway_transfer_with_payload"].into(),
+                ["Destination Chain", &format!("{} ({})", chain, gateway_chain_name(*chain))].into(),
+                ["Contract", contract].into(),
+                ["Nonce", &nonce.to_string()].into(),
+                ["Payload", &payload].into(),
+            ]
+        },
+    };
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_gateway_transfer() {
+        // as serialized by the ibc translator contract
+        let payload = br#"{"gateway_transfer":{"chain":20,"recipient":"b3NtbzFxeXBxeHBxOXFjcnNzemcycHZ4cTZyczB6cWczeXljNWhlbHdzdw==","fee":"0","nonce":7}}"#;
+        assert_eq!(decode_gateway_payload(payload).unwrap(), GatewayPayload::Transfer {
+            chain: 20,
+            recipient: "osmo1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5helwsw".to_string(),
+            fee: "0".to_string(),
+            nonce: 7,
+        });
+        assert_eq!(gateway_chain_name(20), "osmosis");
+    }
+
+    #[test]
+    fn test_decode_gateway_transfer_with_payload() {
+        let payload = br#"{"gateway_transfer_with_payload":{"chain":20,"contract":"b3NtbzF5cXNqeWdleXk1bnp3MnBmOWc0amN0Znc5dWNyenYzbnhzNm52ZGVjOHlhcmswcGE4Y2xzbjl1NGhx","payload":"eyJ3YXNtIjp7ImNvbnRyYWN0Ijoib3NtbzF5cXNqeWdleXk1bnp3MnBmOWc0amN0Znc5dWNyenYzbnhzNm52ZGVjOHlhcmswcGE4Y2xzbjl1NGhxIiwibXNnIjp7fX19","nonce":0}}"#;
+        let expected = GatewayPayload::TransferWithPayload {
+            chain: 20,
+            contract: "osmo1yqsjygeyy5nzw2pf9g4jctfw9ucrzv3nxs6nvdec8yark0pa8clsn9u4hq".to_string(),
+            payload: br#"{"wasm":{"contract":"osmo1yqsjygeyy5nzw2pf9g4jctfw9ucrzv3nxs6nvdec8yark0pa8clsn9u4hq","msg":{}}}"#.to_vec(),
+            nonce: 0,
+        };
+        assert_eq!(decode_gateway_payload(payload).unwrap(), expected);
+        // the older name of the same message
+        let aliased = String::from_utf8(payload.to_vec()).unwrap().replace("gateway_transfer_with_payload", "gateway_ibc_hooks");
+        assert_eq!(decode_gateway_payload(aliased.as_bytes()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_gateway_contract_bech32() {
+        let address = hextoaddress(GATEWAY_CONTRACT).unwrap();
+        assert_eq!(wormchaintobech32(&address), "wormhole14ejqjyq8um4p3xfqj74yld5waqljf88fz25yxnma0cngspxe3les00fpjx");
+    }
+}
@@ -0,0 +1,147 @@
+use clap::ValueEnum;
+use wormhole_sdk::{Address, Amount, Chain, Vaa};
+
+use crate::common::*;
+
+// governance VAAs are emitted by the governance contract on solana
+pub const GOVERNANCE_CHAIN: Chain = Chain::Solana;
+pub const GOVERNANCE_EMITTER: Address = Address([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4,
+]);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GovernanceModule {
+    Core,
+    TokenBridge,
+    NftBridge,
+}
+
+impl GovernanceModule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GovernanceModule::Core => "Core",
+            GovernanceModule::TokenBridge => "TokenBridge",
+            GovernanceModule::NftBridge => "NFTBridge",
+        }
+    }
+
+    pub fn contract_upgrade_action(&self) -> u8 {
+        match self {
+            GovernanceModule::Core => 1,
+            GovernanceModule::TokenBridge | GovernanceModule::NftBridge => 2,
+        }
+    }
+}
+
+pub fn is_governance_vaa<P>(vaa: &Vaa<P>) -> bool {
+    vaa.emitter_chain == GOVERNANCE_CHAIN && vaa.emitter_address == GOVERNANCE_EMITTER
+}
+
+// module, action and target chain of a governance payload
+pub fn decode_governance_header(payload: &[u8]) -> Result<(GovernanceModule, u8, u16), CooError> {
+    let mut reader = PayloadReader::new(payload);
+    let module: [u8; 32] = reader.read_array()?;
+    let name = String::from_utf8_lossy(&module).trim_start_matches('\0').to_string();
+    let module = [GovernanceModule::Core, GovernanceModule::TokenBridge, GovernanceModule::NftBridge].into_iter()
+        .find(|m| m.name() == name)
+        .ok_or_else(|| CooError::ParseError(format!("unknown governance module: {}", name)))?;
+    let action = reader.read_u8()?;
+    let chain = reader.read_u16()?;
+    return Ok((module, action, chain));
+}
+
+pub fn encode_governance_payload(module: &str, action: u8, chain: CooChain, body: &[u8]) -> Result<Vec<u8>, CooError> {
+    // the module name is left-padded with 0s to 32 bytes
+    if module.len() > 32 {
+        return Err(CooError::ParseError(format!("governance module name longer than 32 bytes: {}", module)));
+    }
+    let mut payload = vec![0u8; 32 - module.len()];
+    payload.extend_from_slice(module.as_bytes());
+    payload.push(action);
+    payload.extend_from_slice(&u16::from(chain).to_be_bytes());
+    payload.extend_from_slice(body);
+    return Ok(payload);
+}
+
+pub fn register_chain_payload(module: GovernanceModule, emitter_chain: CooChain, emitter_address: &Address) -> Result<Vec<u8>, CooError> {
+    if module == GovernanceModule::Core {
+        return Err(CooError::ParseError("the core bridge does not register chains".to_string()));
+    }
+    let mut body = u16::from(emitter_chain).to_be_bytes().to_vec();
+    body.extend_from_slice(&emitter_address.0);
+    // chain registrations apply to every chain
+    encode_governance_payload(module.name(), 1, CooChain::from(0u16), &body)
+}
+
+pub fn contract_upgrade_payload(module: GovernanceModule, target_chain: CooChain, new_contract: &Address) -> Result<Vec<u8>, CooError> {
+    encode_governance_payload(module.name(), module.contract_upgrade_action(), target_chain, &new_contract.0)
+}
+
+pub fn guardian_set_upgrade_payload(new_guardian_set_index: u32, guardians: &[[u8; 20]]) -> Result<Vec<u8>, CooError> {
+    if guardians.len() > u8::MAX as usize {
+        return Err(CooError::ParseError(format!("too many guardians: {}", guardians.len())));
+    }
+    let mut body = new_guardian_set_index.to_be_bytes().to_vec();
+    body.push(guardians.len() as u8);
+    for guardian in guardians {
+        body.extend_from_slice(guardian);
+    }
+    // guardian set upgrades apply to every chain
+    encode_governance_payload(GovernanceModule::Core.name(), 2, CooChain::from(0u16), &body)
+}
+
+pub fn set_message_fee_payload(target_chain: CooChain, fee: &Amount) -> Result<Vec<u8>, CooError> {
+    encode_governance_payload(GovernanceModule::Core.name(), 3, target_chain, &fee.0)
+}
+
+pub fn hextoguardian(s: &str) -> Result<[u8; 20], CooError> {
+    let decoded = hextobytes(s)?;
+    let guardian: [u8; 20] = decoded.try_into().map_err(|_| CooError::ParseError(format!("guardian address must be 20 bytes: {}", s)))?;
+    Ok(guardian)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_chain_payload() {
+        let emitter_address = hextoaddress("0x3ee18B2214AFF97000D974cf647E7C347E8fa585").unwrap();
+        let payload = register_chain_payload(GovernanceModule::TokenBridge, CooChain::Inner(Chain::Ethereum), &emitter_address).unwrap();
+        assert_eq!(
+            hex::encode(payload),
+            "000000000000000000000000000000000000000000546f6b656e427269646765010000\
+             0002\
+             0000000000000000000000003ee18b2214aff97000d974cf647e7c347e8fa585"
+        );
+    }
+
+    // "Core" left-padded to 32 bytes
+    const CORE_MODULE: &str = "00000000000000000000000000000000000000000000000000000000436f7265";
+
+    #[test]
+    fn test_contract_upgrade_payload() {
+        let new_contract = Address([0x11; 32]);
+        let payload = contract_upgrade_payload(GovernanceModule::Core, CooChain::Inner(Chain::Ethereum), &new_contract).unwrap();
+        assert_eq!(hex::encode(payload), format!("{}{}{}{}", CORE_MODULE, "01", "0002", "11".repeat(32)));
+        let payload = contract_upgrade_payload(GovernanceModule::NftBridge, CooChain::Inner(Chain::Avalanche), &new_contract).unwrap();
+        assert_eq!(hex::encode(payload), format!("{}{}{}{}", "00000000000000000000000000000000000000000000004e4654427269646765", "02", "0006", "11".repeat(32)));
+    }
+
+    #[test]
+    fn test_guardian_set_upgrade_payload() {
+        let payload = guardian_set_upgrade_payload(5, &[[0xaa; 20], [0xbb; 20]]).unwrap();
+        // applies to every chain, so the target chain is 0
+        assert_eq!(hex::encode(payload), format!("{}{}{}{}{}{}", CORE_MODULE, "02", "0000", "00000005", "02", "aa".repeat(20) + &"bb".repeat(20)));
+        assert!(guardian_set_upgrade_payload(5, &[[0u8; 20]; 256]).is_err());
+    }
+
+    #[test]
+    fn test_set_message_fee_payload() {
+        let mut fee = [0u8; 32];
+        fee[30..].copy_from_slice(&1000u16.to_be_bytes());
+        let payload = set_message_fee_payload(CooChain::Inner(Chain::Solana), &Amount(fee)).unwrap();
+        assert_eq!(hex::encode(payload), format!("{}{}{}{}", CORE_MODULE, "03", "0001", hex::encode(fee)));
+    }
+}
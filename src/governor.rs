@@ -0,0 +1,229 @@
+use comfy_table::Table;
+use reqwest::Url;
+use wormhole_sdk::Chain;
+
+use crate::common::*;
+
+// the governor endpoints of the guardian public rest api. uint64 fields come back as strings and are parsed as numbers,
+// missing fields and chain ids that do not fit a u16 are errors
+
+#[derive(Debug, Clone)]
+pub struct AvailableNotional {
+    pub chain: u16,
+    pub remaining_available_notional: u64,
+    pub notional_limit: u64,
+    pub big_transaction_size: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnqueuedVaa {
+    pub emitter_chain: u16,
+    pub emitter_address: String,
+    pub sequence: u64,
+    pub release_time: u64,
+    pub notional_value: u64,
+    pub tx_hash: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct GovernorToken {
+    pub origin_chain: u16,
+    pub origin_address: String,
+    pub price: f64,
+}
+
+fn query_governor(guardian_url: &Url, path: &str) -> Result<serde_json::Value, CooError> {
+    let query_url = guardian_url.join(path)?;
+    let body = reqwest::blocking::get(query_url)?.text()?;
+    let response: serde_json::Value = serde_json::from_str(&body)?;
+    return Ok(response);
+}
+
+fn governor_entries(response: &serde_json::Value) -> Result<Vec<serde_json::Value>, CooError> {
+    // errors come back as a grpc status instead of entries
+    if let Some(message) = response["message"].as_str().filter(|_| response.get("code").is_some()) {
+        return Err(CooError::ParseError(format!("governor query failed: {}", message)));
+    }
+    Ok(response["entries"].as_array().cloned().unwrap_or_default())
+}
+
+// a renamed field would otherwise be read as 0 or ""
+fn governor_field<'a>(entry: &'a serde_json::Value, name: &str) -> Result<&'a serde_json::Value, CooError> {
+    entry.get(name).ok_or_else(|| CooError::ParseError(format!("governor entry without {}: {}", name, entry)))
+}
+
+fn parse_available_notional(response: &serde_json::Value) -> Result<Vec<AvailableNotional>, CooError> {
+    governor_entries(response)?.iter().map(|e| Ok(AvailableNotional {
+        chain: json_u16(governor_field(e, "chainId")?)?,
+        remaining_available_notional: json_u64(governor_field(e, "remainingAvailableNotional")?),
+        notional_limit: json_u64(governor_field(e, "notionalLimit")?),
+        big_transaction_size: json_u64(governor_field(e, "bigTransactionSize")?),
+    })).collect()
+}
+
+fn parse_enqueued_vaas(response: &serde_json::Value) -> Result<Vec<EnqueuedVaa>, CooError> {
+    governor_entries(response)?.iter().map(|e| Ok(EnqueuedVaa {
+        emitter_chain: json_u16(governor_field(e, "emitterChain")?)?,
+        emitter_address: json_string(governor_field(e, "emitterAddress")?),
+        sequence: json_u64(governor_field(e, "sequence")?),
+        release_time: json_u64(governor_field(e, "releaseTime")?),
+        notional_value: json_u64(governor_field(e, "notionalValue")?),
+        tx_hash: json_string(governor_field(e, "txHash")?),
+    })).collect()
+}
+
+fn parse_token_list(response: &serde_json::Value) -> Result<Vec<GovernorToken>, CooError> {
+    governor_entries(response)?.iter().map(|e| Ok(GovernorToken {
+        origin_chain: json_u16(governor_field(e, "originChainId")?)?,
+        origin_address: json_string(governor_field(e, "originAddress")?),
+        price: governor_field(e, "price")?.as_f64().unwrap_or_default(),
+    })).collect()
+}
+
+fn parse_is_vaa_enqueued(response: &serde_json::Value) -> Result<bool, CooError> {
+    match response["isEnqueued"].as_bool() {
+        Some(enqueued) => Ok(enqueued),
+        None => Err(CooError::ParseError(format!("unexpected is_vaa_enqueued response: {}", response))),
+    }
+}
+
+pub fn available_notional_by_chain(guardian_url: &Url) -> Result<Vec<AvailableNotional>, CooError> {
+    parse_available_notional(&query_governor(guardian_url, "v1/governor/available_notional_by_chain")?)
+}
+
+pub fn enqueued_vaas(guardian_url: &Url) -> Result<Vec<EnqueuedVaa>, CooError> {
+    parse_enqueued_vaas(&query_governor(guardian_url, "v1/governor/enqueued_vaas")?)
+}
+
+pub fn token_list(guardian_url: &Url) -> Result<Vec<GovernorToken>, CooError> {
+    parse_token_list(&query_governor(guardian_url, "v1/governor/token_list")?)
+}
+
+pub fn is_vaa_enqueued(guardian_url: &Url, chain: CooChain, emitter_address: &str, sequence: u64) -> Result<bool, CooError> {
+    let path = format!("v1/governor/is_vaa_enqueued/{}/{}/{}", u16::from(chain), emitter_address, sequence);
+    parse_is_vaa_enqueued(&query_governor(guardian_url, &path)?)
+}
+
+// emitter addresses are compared as 32 byte lowercase hex, whatever the form they were given in
+fn normalize_emitter(emitter_address: &str) -> String {
+    format!("{:0>64}", emitter_address.trim_start_matches("0x")).to_lowercase()
+}
+
+fn find_enqueued_entry(entries: Vec<EnqueuedVaa>, chain: CooChain, emitter_address: &str, sequence: u64) -> Option<EnqueuedVaa> {
+    let emitter_address = normalize_emitter(emitter_address);
+    entries.into_iter().find(|e| {
+        e.emitter_chain == u16::from(chain) && normalize_emitter(&e.emitter_address) == emitter_address && e.sequence == sequence
+    })
+}
+
+// the enqueued entry of a VAA, which carries its release time
+pub fn find_enqueued_vaa(guardian_url: &Url, chain: CooChain, emitter_address: &str, sequence: u64) -> Result<Option<EnqueuedVaa>, CooError> {
+    Ok(find_enqueued_entry(enqueued_vaas(guardian_url)?, chain, emitter_address, sequence))
+}
+
+fn chain_name(chain: u16) -> String {
+    format!("{} ({})", chain, Chain::from(chain))
+}
+
+pub fn pretty_available_notional(entries: &[AvailableNotional]) -> String {
+    let mut table = Table::new();
+    table.set_header(["Chain", "Remaining Notional", "Notional Limit", "Big Transaction Size"]);
+    for e in entries {
+        table.add_row([chain_name(e.chain), e.remaining_available_notional.to_string(), e.notional_limit.to_string(), e.big_transaction_size.to_string()]);
+    }
+    return format!("{table}");
+}
+
+pub fn pretty_enqueued_vaas(entries: &[EnqueuedVaa]) -> String {
+    let mut table = Table::new();
+    table.set_header(["Emitter Chain", "Emitter Address", "Sequence", "Release Time", "Notional Value", "Transaction"]);
+    for e in entries {
+        table.add_row([chain_name(e.emitter_chain), e.emitter_address.clone(), e.sequence.to_string(), e.release_time.to_string(), e.notional_value.to_string(), e.tx_hash.clone()]);
+    }
+    return format!("{table}");
+}
+
+pub fn pretty_token_list(entries: &[GovernorToken]) -> String {
+    let mut table = Table::new();
+    table.set_header(["Origin Chain", "Origin Address", "Price (USD)"]);
+    for e in entries {
+        table.add_row([chain_name(e.origin_chain), e.origin_address.clone(), e.price.to_string()]);
+    }
+    return format!("{table}");
+}
+
+pub fn pretty_enqueued_vaa(entry: &EnqueuedVaa, now: u64) -> String {
+    let mut table = Table::new();
+    table.set_header(["Enqueued by the Governor"]);
+    let remaining = entry.release_time.saturating_sub(now);
+    table.add_row(["Release Time", &format!("{} (in {}h {}m)", entry.release_time, remaining / 3600, remaining % 3600 / 60)]);
+    table.add_row(["Notional Value", &entry.notional_value.to_string()]);
+    table.add_row(["Transaction", &entry.tx_hash]);
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_available_notional() {
+        let response = serde_json::json!({"entries": [
+            {"chainId": 2, "remainingAvailableNotional": "49212880", "notionalLimit": "50000000", "bigTransactionSize": "5000000"},
+        ]});
+        let entries = parse_available_notional(&response).unwrap();
+        assert_eq!(entries[0].chain, 2);
+        assert_eq!(entries[0].remaining_available_notional, 49212880);
+        assert_eq!(entries[0].notional_limit, 50000000);
+        assert_eq!(entries[0].big_transaction_size, 5000000);
+        // a renamed field is an error rather than a 0
+        let response = serde_json::json!({"entries": [{"chain": 2, "remainingAvailableNotional": "1", "notionalLimit": "1", "bigTransactionSize": "1"}]});
+        assert!(parse_available_notional(&response).is_err());
+        let response = serde_json::json!({"code": 12, "message": "Not Implemented", "details": []});
+        assert!(parse_available_notional(&response).is_err());
+    }
+
+    #[test]
+    fn test_parse_enqueued_vaas() {
+        let response = serde_json::json!({"entries": [{
+            "emitterChain": 2,
+            "emitterAddress": "0000000000000000000000003ee18b2214aff97000d974cf647e7c347e8fa585",
+            "sequence": "152394",
+            "releaseTime": 1690000000,
+            "notionalValue": "1200000",
+            "txHash": "0x8e2b7b6c1a7b1e1e0d4c1a1e9b1d2c3b4a5f6e7d8c9b0a1f2e3d4c5b6a7f8e9d",
+        }]});
+        let entries = parse_enqueued_vaas(&response).unwrap();
+        assert_eq!(entries[0].sequence, 152394);
+        assert_eq!(entries[0].release_time, 1690000000);
+        assert_eq!(entries[0].notional_value, 1200000);
+
+        // the emitter may be given with a 0x prefix, unpadded or in upper case
+        let chain = CooChain::from(2u16);
+        assert!(find_enqueued_entry(entries.clone(), chain, "0x3EE18B2214AFF97000D974CF647E7C347E8FA585", 152394).is_some());
+        assert!(find_enqueued_entry(entries.clone(), chain, "3ee18b2214aff97000d974cf647e7c347e8fa585", 152394).is_some());
+        assert!(find_enqueued_entry(entries.clone(), chain, "3ee18b2214aff97000d974cf647e7c347e8fa585", 152395).is_none());
+        assert!(find_enqueued_entry(entries, CooChain::from(4u16), "3ee18b2214aff97000d974cf647e7c347e8fa585", 152394).is_none());
+
+        let response = serde_json::json!({"entries": [{"emitterChain": 65538, "emitterAddress": "", "sequence": "1", "releaseTime": 0, "notionalValue": "0", "txHash": ""}]});
+        assert!(parse_enqueued_vaas(&response).is_err());
+    }
+
+    #[test]
+    fn test_parse_token_list() {
+        let response = serde_json::json!({"entries": [
+            {"originChainId": 2, "originAddress": "0x000000000000000000000000a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", "price": 1.0},
+        ]});
+        let entries = parse_token_list(&response).unwrap();
+        assert_eq!(entries[0].origin_chain, 2);
+        assert_eq!(entries[0].origin_address, "0x000000000000000000000000a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+        assert_eq!(entries[0].price, 1.0);
+    }
+
+    #[test]
+    fn test_parse_is_vaa_enqueued() {
+        assert!(parse_is_vaa_enqueued(&serde_json::json!({"isEnqueued": true})).unwrap());
+        assert!(!parse_is_vaa_enqueued(&serde_json::json!({"isEnqueued": false})).unwrap());
+        assert!(parse_is_vaa_enqueued(&serde_json::json!({"code": 5, "message": "not found"})).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+
+use comfy_table::{Cell, Color, Table};
+use reqwest::Url;
+use wormhole_sdk::Chain;
+
+use crate::common::*;
+
+// heartbeats older than this are shown as stale
+pub const STALE_HEARTBEAT_SECONDS: u64 = 60;
+
+#[derive(Debug, Clone)]
+pub struct ChainHeight {
+    pub chain: u16,
+    pub height: u64,
+    pub error_count: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    pub node_name: String,
+    pub guardian_address: String,
+    pub version: String,
+    // unix seconds, the guardian reports nanoseconds
+    pub timestamp: u64,
+    pub boot_timestamp: u64,
+    pub networks: Vec<ChainHeight>,
+}
+
+fn parse_heartbeat(raw: &serde_json::Value) -> Heartbeat {
+    let networks = raw["networks"].as_array().cloned().unwrap_or_default().iter().map(|n| ChainHeight {
+        chain: json_u64(&n["id"]) as u16,
+        height: json_u64(&n["height"]),
+        error_count: json_u64(&n["errorCount"]),
+    }).collect();
+    Heartbeat {
+        node_name: json_string(&raw["nodeName"]),
+        guardian_address: json_string(&raw["guardianAddr"]).to_lowercase(),
+        version: json_string(&raw["version"]),
+        timestamp: json_u64(&raw["timestamp"]) / 1_000_000_000,
+        boot_timestamp: json_u64(&raw["bootTimestamp"]) / 1_000_000_000,
+        networks,
+    }
+}
+
+// every guardian node gossips, so one node reports the heartbeats of the whole guardian set
+pub fn query_heartbeats(guardian_url: &Url) -> Result<Vec<Heartbeat>, CooError> {
+    let query_url = guardian_url.join("v1/heartbeats")?;
+    let body = reqwest::blocking::get(query_url)?.text()?;
+    let response: serde_json::Value = serde_json::from_str(&body)?;
+    let heartbeats = response["entries"].as_array().cloned().unwrap_or_default().iter()
+        .map(|e| parse_heartbeat(&e["rawHeartbeat"]))
+        .collect();
+    return Ok(heartbeats);
+}
+
+// keeps the most recent heartbeat of each guardian when several nodes are queried
+pub fn merge_heartbeats(heartbeats: Vec<Heartbeat>) -> Vec<Heartbeat> {
+    let mut latest: BTreeMap<String, Heartbeat> = BTreeMap::new();
+    for heartbeat in heartbeats {
+        match latest.get(&heartbeat.guardian_address) {
+            Some(known) if known.timestamp >= heartbeat.timestamp => {},
+            _ => { latest.insert(heartbeat.guardian_address.clone(), heartbeat); },
+        }
+    }
+    let mut merged: Vec<Heartbeat> = latest.into_values().collect();
+    merged.sort_by(|a, b| a.node_name.cmp(&b.node_name));
+    return merged;
+}
+
+// highest height reported for each chain, chains a guardian does not watch report 0 and are ignored
+pub fn highest_heights(heartbeats: &[Heartbeat]) -> BTreeMap<u16, u64> {
+    let mut highest = BTreeMap::new();
+    for network in heartbeats.iter().flat_map(|h| &h.networks).filter(|n| n.height > 0) {
+        let height = highest.entry(network.chain).or_insert(0);
+        *height = std::cmp::max(*height, network.height);
+    }
+    return highest;
+}
+
+// approximate block times in milliseconds, so that the lag of chains with very different block times can be
+// compared. chains that are not listed are assumed to produce a block per second
+fn block_time_ms(chain: u16) -> u64 {
+    match chain {
+        // solana
+        1 => 400,
+        // ethereum
+        2 => 12_000,
+        // bsc
+        4 => 3_000,
+        // polygon, avalanche, optimism, base
+        5 | 6 | 24 | 30 => 2_000,
+        // arbitrum
+        23 => 250,
+        _ => 1_000,
+    }
+}
+
+// the chains on which a guardian is more than max_lag seconds behind the rest, as (chain, blocks, seconds) behind
+pub fn lagging_chains(heartbeat: &Heartbeat, highest: &BTreeMap<u16, u64>, max_lag: u64) -> Vec<(u16, u64, u64)> {
+    heartbeat.networks.iter()
+        .filter(|n| n.height > 0)
+        .filter_map(|n| highest.get(&n.chain).map(|h| h.saturating_sub(n.height)).map(|blocks| (n.chain, blocks, blocks * block_time_ms(n.chain) / 1000)))
+        .filter(|(_, _, seconds)| *seconds > max_lag)
+        .collect()
+}
+
+pub fn pretty_heartbeats(heartbeats: &[Heartbeat], chains: &[CooChain], max_lag: u64, now: u64) -> String {
+    let highest = highest_heights(heartbeats);
+    let chains: Vec<u16> = chains.iter().map(|c| u16::from(*c)).collect();
+    let mut table = Table::new();
+    table.set_header(["Guardian", "Version", "Last Heartbeat", "Boot Time", "Heights", "Lagging"]);
+    for heartbeat in heartbeats {
+        let age = now.saturating_sub(heartbeat.timestamp);
+        let mut last_heartbeat = Cell::new(format!("{} ({}s ago)", heartbeat.timestamp, age));
+        if age > STALE_HEARTBEAT_SECONDS {
+            last_heartbeat = last_heartbeat.fg(Color::Red);
+        }
+        let heights = heartbeat.networks.iter()
+            .filter(|n| chains.is_empty() || chains.contains(&n.chain))
+            .map(|n| format!("{}: {}", Chain::from(n.chain), n.height))
+            .collect::<Vec<String>>()
+            .join("\n");
+        let lagging = lagging_chains(heartbeat, &highest, max_lag).iter()
+            .filter(|(chain, _, _)| chains.is_empty() || chains.contains(chain))
+            .map(|(chain, blocks, seconds)| format!("{}: {} blocks (~{}s) behind", Chain::from(*chain), blocks, seconds))
+            .collect::<Vec<String>>()
+            .join("\n");
+        table.add_row(vec![
+            Cell::new(format!("{}\n{}", heartbeat.node_name, heartbeat.guardian_address)),
+            Cell::new(&heartbeat.version),
+            last_heartbeat,
+            Cell::new(heartbeat.boot_timestamp),
+            Cell::new(heights),
+            Cell::new(lagging).fg(Color::Red),
+        ]);
+    }
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heartbeat(address: &str, timestamp: u64, heights: &[(u16, u64)]) -> Heartbeat {
+        Heartbeat {
+            node_name: address.to_string(),
+            guardian_address: address.to_string(),
+            version: String::new(),
+            timestamp,
+            boot_timestamp: 0,
+            networks: heights.iter().map(|(chain, height)| ChainHeight { chain: *chain, height: *height, error_count: 0 }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_lagging_chains() {
+        let heartbeats = merge_heartbeats(vec![
+            heartbeat("a", 10, &[(2, 1000), (4, 500), (23, 5000)]),
+            heartbeat("b", 10, &[(2, 995), (4, 0), (23, 4900)]),
+            // older heartbeat of a guardian that was also seen through another node
+            heartbeat("b", 5, &[(2, 100), (4, 0), (23, 100)]),
+        ]);
+        assert_eq!(heartbeats.len(), 2);
+        let highest = highest_heights(&heartbeats);
+        assert_eq!(lagging_chains(&heartbeats[0], &highest, 30), vec![]);
+        // 5 ethereum blocks are a minute, 100 arbitrum blocks are 25 seconds
+        assert_eq!(lagging_chains(&heartbeats[1], &highest, 30), vec![(2, 5, 60)]);
+        assert_eq!(lagging_chains(&heartbeats[1], &highest, 60), vec![]);
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use comfy_table::{Table, Row};
+use lazy_static::lazy_static;
+use wormhole_sdk::{Address, Amount, Chain};
+
+use crate::cctp::{CctpDeposit, cctp_domain_chain, decode_cctp_payload, pretty_cctp_payload};
+use crate::common::*;
+use crate::gateway::{GatewayPayload, GATEWAY_CHAIN, GATEWAY_CONTRACT, decode_gateway_payload, pretty_gateway_payload};
+use crate::ntt::{NttManagerPayload, NttMessage, decode_ntt_payload, pretty_ntt_payload};
+
+// inner payloads can carry payloads of their own (e.g. ntt additional payloads), this bounds how deep we follow them
+const MAX_INNER_PAYLOAD_DEPTH: usize = 4;
+
+// decodes the inner payload of a token bridge transfer with payload (payload 3)
+pub type InnerPayloadDecoder = fn(&[u8]) -> Result<InnerPayload, CooError>;
+
+lazy_static! {
+    // inner payload decoders keyed by the recipient contract of the transfer
+    pub static ref INNER_PAYLOAD_DECODERS: HashMap<(CooChain, &'static str), InnerPayloadDecoder> = HashMap::from(
+        [
+            ((CooChain::Inner(Chain::Ethereum), "cafd2f0a35a4459fa40c0517e17e6fa2939441ca"), decode_token_bridge_relayer as InnerPayloadDecoder),
+            ((CooChain::Inner(Chain::Avalanche), "cafd2f0a35a4459fa40c0517e17e6fa2939441ca"), decode_token_bridge_relayer as InnerPayloadDecoder),
+            ((CooChain::Inner(Chain::Ethereum), "AaDA05BD399372f0b0463744C09113c137636f6a"), decode_cctp as InnerPayloadDecoder),
+            ((CooChain::Inner(Chain::Avalanche), "09Fb06A271faFf70A651047395AaEb6265265F13"), decode_cctp as InnerPayloadDecoder),
+            ((CooChain::from(GATEWAY_CHAIN), GATEWAY_CONTRACT), decode_gateway as InnerPayloadDecoder),
+        ]
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum InnerPayload {
+    TokenBridgeRelayer { target_relayer_fee: Amount, to_native_token_amount: Amount, target_recipient: Address },
+    Gateway(GatewayPayload),
+    Cctp(CctpDeposit),
+    Ntt(NttMessage),
+    RawBytes(Vec<u8>),
+}
+
+fn decode_token_bridge_relayer(payload: &[u8]) -> Result<InnerPayload, CooError> {
+    let mut reader = PayloadReader::new(payload);
+    let payload_id = reader.read_u8()?;
+    if payload_id != 1 {
+        return Err(CooError::ParseError(format!("unknown token bridge relayer payload id: {}", payload_id)));
+    }
+    Ok(InnerPayload::TokenBridgeRelayer {
+        target_relayer_fee: reader.read_amount()?,
+        to_native_token_amount: reader.read_amount()?,
+        target_recipient: reader.read_address()?,
+    })
+}
+
+fn decode_gateway(payload: &[u8]) -> Result<InnerPayload, CooError> {
+    Ok(InnerPayload::Gateway(decode_gateway_payload(payload)?))
+}
+
+fn decode_cctp(payload: &[u8]) -> Result<InnerPayload, CooError> {
+    Ok(InnerPayload::Cctp(decode_cctp_payload(payload)?))
+}
+
+fn decode_ntt(payload: &[u8]) -> Result<InnerPayload, CooError> {
+    Ok(InnerPayload::Ntt(decode_ntt_payload(payload)?))
+}
+
+fn infer_inner_payload_decoder(recipient_chain: Chain, recipient: &Address, payload: &[u8]) -> Option<InnerPayloadDecoder> {
+    let recipient = recipient.to_string().to_lowercase();
+    let decoder = INNER_PAYLOAD_DECODERS.iter().find_map(|((chain, address), decoder)| {
+        let map_entry = format!("{:0>64}", address).to_lowercase();
+        if Chain::from(*chain) == recipient_chain && recipient == map_entry {
+            Some(*decoder)
+        } else {
+            None
+        }
+    });
+    if decoder.is_some() {
+        return decoder;
+    }
+    // not a known recipient, so we look at the payload itself
+    if payload.starts_with(b"{\"gateway_") {
+        Some(decode_gateway as InnerPayloadDecoder)
+    } else if payload.starts_with(&crate::ntt::WH_TRANSCEIVER_PAYLOAD_PREFIX) {
+        Some(decode_ntt as InnerPayloadDecoder)
+    } else {
+        None
+    }
+}
+
+pub fn decode_inner_payload(recipient_chain: Chain, recipient: &Address, payload: &[u8]) -> InnerPayload {
+    // a payload that fails to decode is still worth showing, so we fall back to the raw bytes
+    match infer_inner_payload_decoder(recipient_chain, recipient, payload) {
+        Some(decoder) => decoder(payload).unwrap_or_else(|_| InnerPayload::RawBytes(payload.to_vec())),
+        None => InnerPayload::RawBytes(payload.to_vec()),
+    }
+}
+
+// the payload an inner payload carries for its own recipient, if any
+fn nested_payload(inner: &InnerPayload) -> Option<(Chain, Address, Vec<u8>)> {
+    let (chain, recipient, payload) = match inner {
+        InnerPayload::Cctp(deposit) => {
+            let chain = cctp_domain_chain(deposit.target_domain).unwrap_or_else(|| Chain::from(0u16));
+            (chain, deposit.mint_recipient, &deposit.payload)
+        },
+        InnerPayload::Ntt(NttMessage::Transceiver(message)) => match &message.ntt_manager_message.payload {
+            NttManagerPayload::NativeTokenTransfer(transfer) => (Chain::from(transfer.to_chain), transfer.to, &transfer.additional_payload),
+            NttManagerPayload::Unknown(_) => return None,
+        },
+        _ => return None,
+    };
+    match payload.is_empty() {
+        true => None,
+        false => Some((chain, recipient, payload.clone())),
+    }
+}
+
+// the payloads nested inside an already decoded payload, outermost first
+pub fn decode_nested_payloads(inner: &InnerPayload) -> Vec<InnerPayload> {
+    let mut nested: Vec<InnerPayload> = Vec::new();
+    while nested.len() < MAX_INNER_PAYLOAD_DEPTH {
+        match nested_payload(nested.last().unwrap_or(inner)) {
+            Some((chain, recipient, payload)) => nested.push(decode_inner_payload(chain, &recipient, &payload)),
+            None => break,
+        }
+    }
+    return nested;
+}
+
+// decodes the inner payload and everything nested inside it
+pub fn decode_inner_payloads(recipient_chain: Chain, recipient: &Address, payload: &[u8]) -> Vec<InnerPayload> {
+    let inner = decode_inner_payload(recipient_chain, recipient, payload);
+    let mut payloads = vec![inner.clone()];
+    payloads.extend(decode_nested_payloads(&inner));
+    return payloads;
+}
+
+pub fn pretty_inner_payloads(payloads: &[InnerPayload]) -> String {
+    payloads.iter().map(pretty_inner_payload).collect::<Vec<String>>().join("\n")
+}
+
+pub fn pretty_inner_payload(inner: &InnerPayload) -> String {
+    match inner {
+        InnerPayload::TokenBridgeRelayer { target_relayer_fee, to_native_token_amount, target_recipient } => {
+            let mut table = Table::new();
+            table.set_header(["Token Bridge Relayer Payload Information"]);
+            let rows: Vec<Row> = vec![
+                ["Payload Type", "TransferWithRelay"].into(),
+                ["Target Relayer Fee", &amounttodecimal(target_relayer_fee)].into(),
+                ["To Native Token Amount", &amounttodecimal(to_native_token_amount)].into(),
+                ["Target Recipient", &target_recipient.to_string()].into(),
+            ];
+            table.add_rows(rows);
+            return format!("{table}");
+        },
+        InnerPayload::Gateway(gateway_payload) => pretty_gateway_payload(gateway_payload),
+        InnerPayload::Cctp(deposit) => pretty_cctp_payload(deposit),
+        InnerPayload::Ntt(message) => pretty_ntt_payload(message),
+        InnerPayload::RawBytes(payload) => {
+            let mut table = Table::new();
+            table.set_header(["Inner Payload"]);
+            table.add_row(["Raw Bytes", &hex::encode(payload)]);
+            return format!("{table}");
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_nested_payloads() {
+        let mut relayer_payload = vec![1u8];
+        relayer_payload.extend_from_slice(&[0u8; 31]);
+        relayer_payload.push(7);
+        relayer_payload.extend_from_slice(&[0u8; 32]);
+        relayer_payload.extend_from_slice(&[0x44; 32]);
+        // a cctp deposit minted to the token bridge relayer on ethereum, which carries the relayer payload
+        let deposit = CctpDeposit {
+            token: Address([0x11; 32]),
+            amount: Amount([0u8; 32]),
+            source_domain: 1,
+            target_domain: 0,
+            nonce: 1,
+            burn_source: Address([0x22; 32]),
+            mint_recipient: hextoaddress("cafd2f0a35a4459fa40c0517e17e6fa2939441ca").unwrap(),
+            payload: relayer_payload,
+        };
+        let nested = decode_nested_payloads(&InnerPayload::Cctp(deposit.clone()));
+        assert_eq!(nested.len(), 1);
+        match &nested[0] {
+            InnerPayload::TokenBridgeRelayer { target_relayer_fee, target_recipient, .. } => {
+                assert_eq!(amounttodecimal(target_relayer_fee), "7");
+                assert_eq!(*target_recipient, Address([0x44; 32]));
+            },
+            other => panic!("expected a token bridge relayer payload, got {:?}", other),
+        }
+        // nothing is nested in a deposit without payload
+        let empty = CctpDeposit { payload: Vec::new(), ..deposit };
+        assert!(decode_nested_payloads(&InnerPayload::Cctp(empty)).is_empty());
+    }
+}
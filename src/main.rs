@@ -5,11 +5,51 @@ use std::io::Write;
 use clap::{Parser, Subcommand, Args, ValueEnum};
 use lazy_static::lazy_static;
 
+mod bridge;
+mod cctp;
 mod common;
+mod compare;
+mod diagnose;
+mod emitter;
+mod evm;
+mod gateway;
+mod governance;
+mod governor;
+mod guardian;
+mod inner_payload;
+mod ntt;
+mod plugin;
+mod pyth;
+mod relayer;
+mod schema;
+mod signer;
+mod solana;
+mod token;
 mod vaa;
 
-use crate::common::{GUARDIAN_URL, EmitterType, CooChain, PayloadType, hextobytes, base58tobytes, base64tobytes, EMITTERS, PayloadResponse, resolve_emitter_address};
-use crate::vaa::{query_guardian, parse_vaa, pretty_vaa, decode_wormhole_token, decode_wormhole_nft, pretty_nft_payload, pretty_token_payload};
+use serde_wormhole::RawMessage;
+use wormhole_sdk::Chain;
+
+use crate::common::{CooError, GUARDIAN_URL, RPC_ENDPOINTS, EmitterType, CooChain, PayloadType, hextobytes, base58tobytes, base64tobytes, bytestobase58, bytestobase64, hextoaddress, stringtoamount, PayloadResponse, resolve_emitter_address};
+use crate::bridge::{guardian_set, core_bridge_chains, compare_bridge_states, pretty_bridge_state, pretty_bridge_comparison};
+use crate::cctp::{decode_cctp_payload, pretty_cctp_payload};
+use crate::inner_payload::{InnerPayload, decode_nested_payloads, pretty_inner_payloads};
+use crate::compare::{query_guardians, summarize_copies, bodies_match, merge_signatures, quorum, pretty_vaa_comparison};
+use crate::diagnose::{diagnose_missing_vaa, pretty_not_found_diagnosis};
+use crate::emitter::{derive_emitter_address, emitter_derivation, pretty_emitter_address};
+use crate::evm::{evm_provider, evm_contract, evm_transfer_status, pretty_redemption_status, RedemptionStatus, redeem_call, evm_wrapped_asset, evm_wallet, evm_send, evm_receipt, evm_message_fee, evm_core_message_fee, publish_message_calldata, evm_core_state, parse_log_message_published, hextoevmaddress, pretty_send_result, verify_call, pretty_redeem_call};
+use crate::governance::{GovernanceModule, GOVERNANCE_CHAIN, GOVERNANCE_EMITTER, is_governance_vaa, decode_governance_header, encode_governance_payload, register_chain_payload, contract_upgrade_payload, guardian_set_upgrade_payload, set_message_fee_payload, hextoguardian};
+use crate::governor::{available_notional_by_chain, enqueued_vaas, token_list, is_vaa_enqueued, pretty_available_notional, pretty_enqueued_vaas, pretty_token_list, pretty_enqueued_vaa};
+use crate::guardian::{query_heartbeats, merge_heartbeats, pretty_heartbeats};
+use crate::ntt::{decode_ntt_payload, pretty_ntt_payload};
+use crate::plugin::{load_plugins, find_plugin, run_plugin};
+use crate::pyth::{decode_pyth_payload, pretty_pyth_payload};
+use crate::relayer::{decode_relayer_payload, pretty_relayer_payload};
+use crate::schema::{load_schemas, find_schema, decode_schema_payload, pretty_schema_payload};
+use crate::solana::{parse_posted_account, pretty_posted_account, base58topubkey, derive_address, CORE_BRIDGE_PROGRAM, TOKEN_BRIDGE_PROGRAM, NFT_BRIDGE_PROGRAM, solana_account_exists, solana_first_signature, claim_address, bridge_config_seeds, guardian_set_seeds, fee_collector_seeds, sequence_seeds, posted_vaa_seeds, claim_seeds, emitter_seeds, wrapped_mint_seeds, wrapped_meta_seeds, custody_seeds, pretty_program_address};
+use crate::signer::{parse_guardian_keys, sign_vaa, vaa_digest};
+use crate::token::{wrapped_asset_address, token_bridge_chains, pretty_wrapped_assets, recipienttoaddress, TransferCall, transfer_calldata, approve_calldata, attest_calldata};
+use crate::vaa::{query_guardian, wait_for_vaa, parse_vaa, pretty_vaa, decode_wormhole_token, decode_wormhole_nft, pretty_nft_payload, pretty_token_payload, CooVaaHeader, build_vaa, encode_token_payload, encode_nft_payload, infer_payload_type, edit_vaa_header, edit_vaa_payload, transfer_target};
 
 lazy_static! {
     static ref DEFAULT_APP_PATH: PathBuf = dirs::home_dir().unwrap().join(".coo");
@@ -30,6 +70,22 @@ struct Cli {
 enum CooCommand {
     /// Your friendly all-in-one toolkit to view or manipulate Wormhole VAAs.
     Vaa(VaaArgs),
+    /// Generates governance VAAs signed by guardian keys (defaults to the devnet guardian).
+    Governance(GovernanceArgs),
+    /// Derives Solana Wormhole program addresses offline.
+    Address(AddressArgs),
+    /// Derives the Wormhole emitter address of a program on non-EVM chains.
+    Emitter(EmitterArgs),
+    /// Looks up token bridge assets across chains.
+    Token(TokenArgs),
+    /// Publishes messages through the core bridge of an EVM chain.
+    Message(MessageArgs),
+    /// Reads the on-chain state of the core bridges.
+    Bridge(BridgeArgs),
+    /// Queries the Governor of the guardians.
+    Governor(GovernorArgs),
+    /// Checks the heartbeats of the guardian nodes.
+    Guardian(GuardianArgs),
 }
 
 #[derive(Debug, Args)]
@@ -46,6 +102,16 @@ enum VaaCommand {
     Decode(VaaDecodeArgs),
     /// List VAAs that have been queried.
     List,
+    /// Builds a VAA locally and signs it with guardian keys (defaults to the devnet guardian).
+    Build(VaaBuildArgs),
+    /// Edits fields of an existing VAA and re-encodes it.
+    Edit(VaaEditArgs),
+    /// Checks whether a token or NFT transfer VAA has been redeemed on its target chain.
+    Status(VaaStatusArgs),
+    /// Submits a VAA to its entrypoint on an EVM chain (transfers, asset meta, governance).
+    Redeem(VaaRedeemArgs),
+    /// Encodes the EVM calldata that submits a VAA, without sending it.
+    Calldata(VaaCalldataArgs),
 }
 
 #[derive(Debug, Args)]
@@ -59,6 +125,12 @@ struct VaaQueryArgs {
     emitter: EmitterType,
     /// Sequence number of the VAA
     sequence: u64,
+    #[arg(long, value_delimiter = ',')]
+    /// Comma separated Guardian RPC URLs to fetch the VAA from and compare, instead of the single guardian URL
+    compare: Vec<String>,
+    #[arg(long, requires = "compare")]
+    /// Merges the valid signatures of every compared VAA into one VAA
+    merge: bool,
 }
 
 #[derive(Debug, Args)]
@@ -69,10 +141,704 @@ struct VaaDecodeArgs {
     #[arg(value_enum, short, long, default_value_t = PayloadType::SmartInfer)]
     /// Specifies the payload type for the VAA. If not specified, the payload type will be inferred from the VAA.
     payload_type: PayloadType,
+    #[arg(long)]
+    /// Input is Solana PostedVAA or PostedMessage account data (e.g. from getAccountInfo) instead of a signed VAA
+    solana_account: bool,
+    #[arg(long, requires = "solana_account")]
+    /// Guardian set index of the VAA rebuilt from a Solana account, which the account does not store. Defaults to 0.
+    guardian_set_index: Option<u32>,
+    #[arg(long)]
+    /// Looks up the wrapped token that the recipient of a transfer receives on the destination chain
+    wrapped: bool,
+    /// Input (VAA data or path)
+    data: String,
+}
+
+#[derive(Debug, Args)]
+struct VaaHeaderArgs {
+    #[arg(long)]
+    /// Chain ID of the emitter aka source chain (can be id or name)
+    chain_id: CooChain,
+    #[arg(long)]
+    /// Emitter contract address or emitter type
+    emitter: EmitterType,
+    #[arg(long, default_value_t = 0)]
+    /// Sequence number of the VAA
+    sequence: u64,
+    #[arg(long, default_value_t = 0)]
+    /// Nonce of the VAA
+    nonce: u32,
+    #[arg(long)]
+    /// Unix timestamp of the VAA. Defaults to the current time.
+    timestamp: Option<u32>,
+    #[arg(long, default_value_t = 1)]
+    /// Consistency level of the VAA
+    consistency_level: u8,
+    #[arg(long, default_value_t = 0)]
+    /// Guardian set index of the VAA
+    guardian_set_index: u32,
+    #[arg(long = "guardian-key")]
+    /// Guardian private key (hex) to sign with, in guardian set order. Can be repeated. Defaults to the devnet guardian key.
+    guardian_keys: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+struct VaaBuildArgs {
+    #[command(flatten)]
+    header: VaaHeaderArgs,
+    #[arg(value_enum, short, long, default_value_t = VaaDataFormat::Base64)]
+    /// Output format of the VAA. Path saves the VAA into the cache.
+    output_format: VaaDataFormat,
+    #[clap(subcommand)]
+    payload: VaaBuildPayload,
+}
+
+#[derive(Debug, Subcommand)]
+enum VaaBuildPayload {
+    /// Raw payload bytes given as hex.
+    Raw {
+        /// Payload bytes (hex)
+        payload: String,
+    },
+    /// Token bridge transfer payload.
+    TokenTransfer(TokenTransferArgs),
+    /// Token bridge transfer with payload.
+    TokenTransferPayload(TokenTransferPayloadArgs),
+    /// Token bridge asset meta payload.
+    AssetMeta(AssetMetaArgs),
+    /// NFT bridge transfer payload.
+    NftTransfer(NftTransferArgs),
+    /// Governance payload with an arbitrary module and action.
+    Governance(GovernancePayloadArgs),
+}
+
+#[derive(Debug, Args)]
+struct TokenTransferArgs {
+    #[arg(long)]
+    /// Amount to transfer (decimal or 0x prefixed hex)
+    amount: String,
+    #[arg(long)]
+    /// Token address on the origin chain (hex)
+    token_address: String,
+    #[arg(long)]
+    /// Origin chain of the token (can be id or name)
+    token_chain: CooChain,
+    #[arg(long)]
+    /// Recipient address (hex)
+    recipient: String,
+    #[arg(long)]
+    /// Recipient chain (can be id or name)
+    recipient_chain: CooChain,
+    #[arg(long, default_value = "0")]
+    /// Relayer fee (decimal or 0x prefixed hex)
+    fee: String,
+}
+
+#[derive(Debug, Args)]
+struct TokenTransferPayloadArgs {
+    #[arg(long)]
+    /// Amount to transfer (decimal or 0x prefixed hex)
+    amount: String,
+    #[arg(long)]
+    /// Token address on the origin chain (hex)
+    token_address: String,
+    #[arg(long)]
+    /// Origin chain of the token (can be id or name)
+    token_chain: CooChain,
+    #[arg(long)]
+    /// Recipient address (hex)
+    recipient: String,
+    #[arg(long)]
+    /// Recipient chain (can be id or name)
+    recipient_chain: CooChain,
+    #[arg(long)]
+    /// Sender address (hex)
+    sender_address: String,
+    #[arg(long, default_value = "")]
+    /// Inner payload (hex)
+    payload: String,
+}
+
+#[derive(Debug, Args)]
+struct AssetMetaArgs {
+    #[arg(long)]
+    /// Token address on the origin chain (hex)
+    token_address: String,
+    #[arg(long)]
+    /// Origin chain of the token (can be id or name)
+    token_chain: CooChain,
+    #[arg(long)]
+    /// Token decimals
+    decimals: u8,
+    #[arg(long)]
+    /// Token symbol
+    symbol: String,
+    #[arg(long)]
+    /// Token name
+    name: String,
+}
+
+#[derive(Debug, Args)]
+struct NftTransferArgs {
+    #[arg(long)]
+    /// NFT address on the origin chain (hex)
+    nft_address: String,
+    #[arg(long)]
+    /// Origin chain of the NFT (can be id or name)
+    nft_chain: CooChain,
+    #[arg(long)]
+    /// NFT symbol
+    symbol: String,
+    #[arg(long)]
+    /// NFT name
+    name: String,
+    #[arg(long)]
+    /// Token ID (decimal or 0x prefixed hex)
+    token_id: String,
+    #[arg(long)]
+    /// Token URI
+    uri: String,
+    #[arg(long)]
+    /// Destination address (hex)
+    to: String,
+    #[arg(long)]
+    /// Destination chain (can be id or name)
+    to_chain: CooChain,
+}
+
+#[derive(Debug, Args)]
+struct GovernancePayloadArgs {
+    #[arg(long)]
+    /// Governance module name (e.g. Core, TokenBridge, NFTBridge)
+    module: String,
+    #[arg(long)]
+    /// Governance action ID
+    action: u8,
+    #[arg(long, default_value = "0")]
+    /// Target chain of the governance action, 0 for all chains (can be id or name)
+    target_chain: CooChain,
+    #[arg(long, default_value = "")]
+    /// Action specific body (hex)
+    body: String,
+}
+
+#[derive(Debug, Args)]
+struct VaaEditArgs {
+    #[arg(value_enum, short, long, default_value_t = VaaDataFormat::Base64)]
+    /// VAA data format
+    data_format: VaaDataFormat,
+    #[arg(value_enum, short, long, default_value_t = PayloadType::SmartInfer)]
+    /// Specifies the payload type for the VAA. If not specified, the payload type will be inferred from the VAA.
+    payload_type: PayloadType,
+    #[arg(long = "set")]
+    /// Field to change as key=value (e.g. amount=1000, sequence=5). Can be repeated.
+    set: Vec<String>,
+    #[arg(value_enum, long, default_value_t = SignatureMode::Keep)]
+    /// What to do with the signatures of the VAA
+    signatures: SignatureMode,
+    #[arg(long = "guardian-key")]
+    /// Guardian private key (hex) to re-sign with, in guardian set order. Can be repeated. Defaults to the devnet guardian key.
+    guardian_keys: Vec<String>,
+    #[arg(value_enum, short, long, default_value_t = VaaDataFormat::Base64)]
+    /// Output format of the VAA. Path saves the VAA into the cache.
+    output_format: VaaDataFormat,
+    /// Input (VAA data or path)
+    data: String,
+}
+
+#[derive(Debug, Args)]
+struct VaaStatusArgs {
+    #[arg(value_enum, short, long, default_value_t = VaaDataFormat::Base64)]
+    /// VAA data format
+    data_format: VaaDataFormat,
+    #[arg(value_enum, short, long, default_value_t = PayloadType::SmartInfer)]
+    /// Specifies the payload type for the VAA. If not specified, the payload type will be inferred from the VAA.
+    payload_type: PayloadType,
+    #[arg(long)]
+    /// RPC URL of the target chain. Defaults to the known endpoint of the chain.
+    rpc_url: Option<String>,
+    #[arg(long)]
+    /// Bridge contract (EVM, hex) or program (Solana, base58) on the target chain. Defaults to the mainnet deployment.
+    contract: Option<String>,
+    #[arg(long, default_value_t = 0)]
+    /// First block to search for the redemption transaction on EVM chains
+    from_block: u64,
+    /// Input (VAA data or path)
+    data: String,
+}
+
+#[derive(Debug, Args)]
+struct EvmSignerArgs {
+    #[arg(long)]
+    /// RPC URL of the chain. Defaults to the known endpoint of the chain.
+    rpc_url: Option<String>,
+    #[arg(long, env = "COO_PRIVATE_KEY", hide_env_values = true)]
+    /// Private key (hex) to sign the transaction with. Prefer the environment variable to keep it out of the shell history.
+    private_key: Option<String>,
+    #[arg(long)]
+    /// Keystore file to sign the transaction with
+    keystore: Option<PathBuf>,
+    #[arg(long, env = "COO_KEYSTORE_PASSWORD", hide_env_values = true)]
+    /// Password of the keystore
+    keystore_password: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct VaaRedeemArgs {
+    #[arg(value_enum, short, long, default_value_t = VaaDataFormat::Base64)]
+    /// VAA data format
+    data_format: VaaDataFormat,
+    #[arg(value_enum, short, long, default_value_t = PayloadType::SmartInfer)]
+    /// Specifies the payload type for the VAA. If not specified, the payload type will be inferred from the VAA.
+    payload_type: PayloadType,
+    #[arg(long)]
+    /// EVM chain to redeem on (can be id or name)
+    chain: CooChain,
+    #[command(flatten)]
+    signer: EvmSignerArgs,
+    #[arg(long)]
+    /// Contract (hex) to submit the VAA to. Defaults to the mainnet bridge matching the VAA.
+    contract: Option<String>,
+    #[arg(long)]
+    /// Unwraps the native token of the chain (completeTransferAndUnwrapETH, or completeTransferAndUnwrapETHWithPayload for transfers with payload)
+    unwrap: bool,
+    #[arg(long)]
+    /// Simulates the transaction with eth_call instead of sending it
+    dry_run: bool,
+    /// Input (VAA data or path)
+    data: String,
+}
+
+#[derive(Debug, Args)]
+struct VaaCalldataArgs {
+    #[arg(value_enum, short, long, default_value_t = VaaDataFormat::Base64)]
+    /// VAA data format
+    data_format: VaaDataFormat,
+    #[arg(value_enum, short, long, default_value_t = PayloadType::SmartInfer)]
+    /// Specifies the payload type for the VAA. If not specified, the payload type will be inferred from the VAA.
+    payload_type: PayloadType,
+    #[arg(long)]
+    /// EVM chain the VAA is submitted on (can be id or name). Defaults to the target chain of the VAA.
+    chain: Option<CooChain>,
+    #[arg(long)]
+    /// Contract (hex) the VAA is submitted to. Defaults to the mainnet contract matching the VAA.
+    contract: Option<String>,
+    #[arg(long)]
+    /// Unwraps the native token of the chain (completeTransferAndUnwrapETH, or completeTransferAndUnwrapETHWithPayload for transfers with payload)
+    unwrap: bool,
+    #[arg(long)]
+    /// Updates an already created wrapped token (updateWrapped instead of createWrapped)
+    update_wrapped: bool,
+    #[arg(long)]
+    /// Only verifies the VAA with the core bridge (parseAndVerifyVM)
+    verify: bool,
     /// Input (VAA data or path)
     data: String,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SignatureMode {
+    /// Removes all signatures
+    Drop,
+    /// Keeps the original signatures (the VAA will no longer verify)
+    Keep,
+    /// Re-signs the VAA with the given guardian keys
+    Resign,
+}
+
+#[derive(Debug, Args)]
+struct GovernanceArgs {
+    #[clap(subcommand)]
+    governance_command: Option<GovernanceCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+enum GovernanceCommand {
+    /// Registers the emitter of a token or NFT bridge on another chain.
+    RegisterChain(GovernanceRegisterChainArgs),
+    /// Upgrades the core, token or NFT bridge contract on a chain.
+    ContractUpgrade(GovernanceContractUpgradeArgs),
+    /// Upgrades the guardian set of the core bridge.
+    GuardianSetUpgrade(GovernanceGuardianSetUpgradeArgs),
+    /// Sets the message fee of the core bridge on a chain.
+    SetMessageFee(GovernanceSetMessageFeeArgs),
+}
+
+#[derive(Debug, Args)]
+struct GovernanceVaaArgs {
+    #[arg(long, default_value_t = 0)]
+    /// Sequence number of the VAA
+    sequence: u64,
+    #[arg(long, default_value_t = 0)]
+    /// Nonce of the VAA
+    nonce: u32,
+    #[arg(long)]
+    /// Unix timestamp of the VAA. Defaults to the current time.
+    timestamp: Option<u32>,
+    #[arg(long, default_value_t = 32)]
+    /// Consistency level of the VAA
+    consistency_level: u8,
+    #[arg(long, default_value_t = 0)]
+    /// Guardian set index of the VAA
+    guardian_set_index: u32,
+    #[arg(long = "guardian-key")]
+    /// Guardian private key (hex) to sign with, in guardian set order. Can be repeated. Defaults to the devnet guardian key.
+    guardian_keys: Vec<String>,
+    #[arg(value_enum, short, long, default_value_t = VaaDataFormat::Base64)]
+    /// Output format of the VAA. Path saves the VAA into the cache.
+    output_format: VaaDataFormat,
+}
+
+#[derive(Debug, Args)]
+struct GovernanceRegisterChainArgs {
+    #[command(flatten)]
+    vaa: GovernanceVaaArgs,
+    #[arg(value_enum, long, default_value_t = GovernanceModule::TokenBridge)]
+    /// Module that registers the chain
+    module: GovernanceModule,
+    /// Chain of the emitter to register (can be id or name)
+    emitter_chain: CooChain,
+    /// Emitter address to register (hex)
+    emitter_address: String,
+}
+
+#[derive(Debug, Args)]
+struct GovernanceContractUpgradeArgs {
+    #[command(flatten)]
+    vaa: GovernanceVaaArgs,
+    #[arg(value_enum, long, default_value_t = GovernanceModule::Core)]
+    /// Module of the contract to upgrade
+    module: GovernanceModule,
+    /// Chain of the contract to upgrade (can be id or name)
+    target_chain: CooChain,
+    /// Address of the new implementation (hex)
+    new_contract: String,
+}
+
+#[derive(Debug, Args)]
+struct GovernanceGuardianSetUpgradeArgs {
+    #[command(flatten)]
+    vaa: GovernanceVaaArgs,
+    #[arg(long)]
+    /// Index of the new guardian set. Defaults to the guardian set index of the VAA plus one.
+    new_guardian_set_index: Option<u32>,
+    #[arg(required = true)]
+    /// Guardian addresses (20 byte hex) of the new guardian set, in order
+    guardians: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+struct GovernanceSetMessageFeeArgs {
+    #[command(flatten)]
+    vaa: GovernanceVaaArgs,
+    /// Chain of the core bridge (can be id or name)
+    target_chain: CooChain,
+    /// New message fee (decimal or 0x prefixed hex)
+    fee: String,
+}
+
+#[derive(Debug, Args)]
+struct AddressArgs {
+    #[clap(subcommand)]
+    address_command: Option<AddressCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+enum AddressCommand {
+    /// Derives a program derived address of the Solana core, token or NFT bridge.
+    Derive(AddressDeriveArgs),
+}
+
+#[derive(Debug, Args)]
+struct AddressDeriveArgs {
+    #[arg(long)]
+    /// Program id (base58) to derive from. Defaults to the mainnet program of the account.
+    program: Option<String>,
+    #[clap(subcommand)]
+    account: AddressDeriveAccount,
+}
+
+#[derive(Debug, Subcommand)]
+enum AddressDeriveAccount {
+    /// Core bridge config account
+    BridgeConfig,
+    /// Core bridge guardian set account
+    GuardianSet {
+        /// Guardian set index
+        index: u32,
+    },
+    /// Core bridge fee collector account
+    FeeCollector,
+    /// Core bridge sequence account of an emitter
+    Sequence {
+        /// Emitter address (base58)
+        emitter: String,
+    },
+    /// Core bridge posted VAA account
+    PostedVaa {
+        /// Hash of the VAA body (hex), i.e. keccak256 of the body
+        hash: String,
+    },
+    /// Claim account of a redeemed VAA, under the token bridge unless --program is given
+    Claim {
+        /// Emitter chain of the VAA (can be id or name)
+        emitter_chain: CooChain,
+        /// Emitter address of the VAA (hex)
+        emitter_address: String,
+        /// Sequence of the VAA
+        sequence: u64,
+    },
+    /// Emitter of the token bridge, or of the program given with --program
+    Emitter,
+    /// Token bridge wrapped mint of a foreign token
+    WrappedMint {
+        /// Origin chain of the token (can be id or name)
+        token_chain: CooChain,
+        /// Origin address of the token (hex)
+        token_address: String,
+    },
+    /// Token bridge wrapped metadata account of a wrapped mint
+    WrappedMeta {
+        /// Wrapped mint (base58)
+        mint: String,
+    },
+    /// Token bridge custody account of a native mint
+    Custody {
+        /// Native mint (base58)
+        mint: String,
+    },
+}
+
+#[derive(Debug, Args)]
+struct EmitterArgs {
+    #[clap(subcommand)]
+    emitter_command: Option<EmitterCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+enum EmitterCommand {
+    /// Derives the emitter address (hex) of a program, usable as the emitter of `vaa query`.
+    Derive(EmitterDeriveArgs),
+}
+
+#[derive(Debug, Args)]
+struct EmitterDeriveArgs {
+    #[arg(long)]
+    /// Chain of the program (can be id or name)
+    chain: CooChain,
+    #[arg(long)]
+    /// Solana program id (base58), Aptos emitter capability id, Sui emitter cap object id or CosmWasm contract address (bech32)
+    program: String,
+}
+
+#[derive(Debug, Args)]
+struct TokenArgs {
+    #[clap(subcommand)]
+    token_command: Option<TokenCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+enum TokenCommand {
+    /// Lists the wrapped address of a token on every known token bridge chain.
+    Wrapped(TokenWrappedArgs),
+    /// Transfers a token (or the native token) through the token bridge of an EVM chain.
+    Transfer(TokenTransferArgs),
+    /// Attests a token through the token bridge of an EVM chain.
+    Attest(TokenAttestArgs),
+}
+
+#[derive(Debug, Args)]
+struct TokenWrappedArgs {
+    /// Origin chain of the token (can be id or name)
+    origin_chain: CooChain,
+    /// Origin address of the token (hex)
+    origin_address: String,
+}
+
+#[derive(Debug, Args)]
+struct VaaWaitArgs {
+    #[arg(short, long, default_value = GUARDIAN_URL)]
+    /// Wormhole Guardian RPC URL. Defaults to mainnet, point it at the devnet guardian (e.g. http://localhost:7071 under Tilt) when publishing on a local chain.
+    guardian_url_str: String,
+    #[arg(long, default_value_t = 1200)]
+    /// Seconds to wait for the guardians to sign the VAA
+    timeout: u64,
+    #[arg(long)]
+    /// Only publishes the message, without waiting for the VAA. Use it on a local chain (e.g. anvil) that no guardian watches.
+    no_wait: bool,
+}
+
+#[derive(Debug, Args)]
+struct TokenTransferArgs {
+    #[command(flatten)]
+    signer: EvmSignerArgs,
+    #[command(flatten)]
+    wait: VaaWaitArgs,
+    #[arg(long)]
+    /// EVM chain to transfer from (can be id or name)
+    chain: CooChain,
+    #[arg(long)]
+    /// Token bridge contract (hex). Defaults to the mainnet token bridge of the chain.
+    contract: Option<String>,
+    #[arg(long)]
+    /// Token (hex) to transfer. Transfers the native token if not given.
+    token: Option<String>,
+    #[arg(long)]
+    /// Amount to transfer in the smallest unit of the token (decimal or 0x prefixed hex)
+    amount: String,
+    #[arg(long)]
+    /// Chain of the recipient (can be id or name)
+    recipient_chain: CooChain,
+    #[arg(long)]
+    /// Recipient address (hex, or base58 for Solana)
+    recipient: String,
+    #[arg(long, default_value = "0")]
+    /// Relayer fee taken out of the amount
+    fee: String,
+    #[arg(long, default_value_t = 0)]
+    /// Nonce of the message
+    nonce: u32,
+    #[arg(long)]
+    /// Payload (hex) for the recipient contract. Sends a transfer with payload.
+    payload: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct TokenAttestArgs {
+    #[command(flatten)]
+    signer: EvmSignerArgs,
+    #[command(flatten)]
+    wait: VaaWaitArgs,
+    #[arg(long)]
+    /// EVM chain of the token (can be id or name)
+    chain: CooChain,
+    #[arg(long)]
+    /// Token bridge contract (hex). Defaults to the mainnet token bridge of the chain.
+    contract: Option<String>,
+    #[arg(long)]
+    /// Token (hex) to attest
+    token: String,
+    #[arg(long, default_value_t = 0)]
+    /// Nonce of the message
+    nonce: u32,
+}
+
+#[derive(Debug, Args)]
+struct MessageArgs {
+    #[clap(subcommand)]
+    message_command: Option<MessageCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+enum MessageCommand {
+    /// Publishes an arbitrary payload with the signer as the emitter.
+    Publish(MessagePublishArgs),
+}
+
+#[derive(Debug, Args)]
+struct MessagePublishArgs {
+    #[command(flatten)]
+    signer: EvmSignerArgs,
+    #[command(flatten)]
+    wait: VaaWaitArgs,
+    #[arg(long)]
+    /// EVM chain to publish on (can be id or name)
+    chain: CooChain,
+    #[arg(long)]
+    /// Core bridge contract (hex). Defaults to the mainnet core bridge of the chain.
+    contract: Option<String>,
+    #[arg(long)]
+    /// Payload of the message (hex)
+    payload: String,
+    #[arg(long, default_value_t = 0)]
+    /// Nonce of the message
+    nonce: u32,
+    #[arg(long, default_value_t = 1)]
+    /// Consistency level of the message
+    consistency: u8,
+}
+
+#[derive(Debug, Args)]
+struct BridgeArgs {
+    #[clap(subcommand)]
+    bridge_command: Option<BridgeCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+enum BridgeCommand {
+    /// Reads guardian set, message fee and governance of core bridges and compares them across chains.
+    State(BridgeStateArgs),
+}
+
+#[derive(Debug, Args)]
+struct BridgeStateArgs {
+    #[arg(long)]
+    /// EVM chain to read (can be id or name). Can be repeated. Defaults to every known core bridge.
+    chain: Vec<CooChain>,
+    #[arg(long)]
+    /// RPC URL of the chain, when a single chain is given
+    rpc_url: Option<String>,
+    #[arg(long)]
+    /// Core bridge contract (hex), when a single chain is given
+    contract: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct GovernorArgs {
+    #[arg(short, long, default_value = GUARDIAN_URL)]
+    /// Wormhole Guardian RPC URL
+    guardian_url_str: String,
+    #[clap(subcommand)]
+    governor_command: Option<GovernorCommand>,
+}
+
+#[derive(Debug, Args)]
+struct GuardianArgs {
+    #[clap(subcommand)]
+    guardian_command: Option<GuardianCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+enum GuardianCommand {
+    /// Shows version, last heartbeat and block heights of every guardian, highlighting the ones that fall behind.
+    Health(GuardianHealthArgs),
+}
+
+#[derive(Debug, Args)]
+struct GuardianHealthArgs {
+    #[arg(short, long, default_value = GUARDIAN_URL)]
+    /// Wormhole Guardian RPC URL. Can be repeated to combine the heartbeats seen by several nodes.
+    guardian_url_str: Vec<String>,
+    #[arg(long)]
+    /// Only show the heights of this chain (can be id or name). Can be repeated.
+    chain: Vec<CooChain>,
+    #[arg(long, default_value_t = 60)]
+    /// Seconds behind the highest guardian after which a chain is highlighted, estimated from the block time of each chain
+    max_lag: u64,
+}
+
+#[derive(Debug, Subcommand)]
+enum GovernorCommand {
+    /// Lists the notional value that can still be transferred out of each chain.
+    AvailableNotional,
+    /// Lists the VAAs held back by the Governor.
+    EnqueuedVaas,
+    /// Lists the tokens the Governor tracks and their prices.
+    TokenList,
+    /// Checks whether a VAA is held back by the Governor.
+    IsVaaEnqueued(GovernorIsVaaEnqueuedArgs),
+}
+
+#[derive(Debug, Args)]
+struct GovernorIsVaaEnqueuedArgs {
+    /// Chain ID of the emitter aka source chain (can be id or name)
+    chain_id: CooChain,
+    /// Emitter contract address or emitter type
+    emitter: EmitterType,
+    /// Sequence number of the VAA
+    sequence: u64,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum VaaDataFormat{
     Base64,
@@ -103,11 +869,113 @@ fn main() {
                 Some(VaaCommand::List) => {
                     cli_vaa_list(&app_path);
                 },
+                Some(VaaCommand::Build(vaa_build_args)) => {
+                    cli_vaa_build(vaa_build_args, &app_path);
+                },
+                Some(VaaCommand::Edit(vaa_edit_args)) => {
+                    cli_vaa_edit(vaa_edit_args, &app_path);
+                },
+                Some(VaaCommand::Status(vaa_status_args)) => {
+                    cli_vaa_status(vaa_status_args, &app_path);
+                },
+                Some(VaaCommand::Redeem(vaa_redeem_args)) => {
+                    cli_vaa_redeem(vaa_redeem_args, &app_path);
+                },
+                Some(VaaCommand::Calldata(vaa_calldata_args)) => {
+                    cli_vaa_calldata(vaa_calldata_args, &app_path);
+                },
                 None => {
                     println!("No VAA command specified");
                 }
             }
         },
+        Some(CooCommand::Governance(governance_args)) => {
+            match governance_args.governance_command {
+                Some(governance_command) => {
+                    cli_governance(governance_command, &app_path);
+                },
+                None => {
+                    println!("No governance command specified");
+                }
+            }
+        },
+        Some(CooCommand::Address(address_args)) => {
+            match address_args.address_command {
+                Some(AddressCommand::Derive(address_derive_args)) => {
+                    cli_address_derive(address_derive_args);
+                },
+                None => {
+                    println!("No address command specified");
+                }
+            }
+        },
+        Some(CooCommand::Emitter(emitter_args)) => {
+            match emitter_args.emitter_command {
+                Some(EmitterCommand::Derive(emitter_derive_args)) => {
+                    cli_emitter_derive(emitter_derive_args);
+                },
+                None => {
+                    println!("No emitter command specified");
+                }
+            }
+        },
+        Some(CooCommand::Token(token_args)) => {
+            match token_args.token_command {
+                Some(TokenCommand::Wrapped(token_wrapped_args)) => {
+                    cli_token_wrapped(token_wrapped_args);
+                },
+                Some(TokenCommand::Transfer(token_transfer_args)) => {
+                    cli_token_transfer(token_transfer_args, &app_path);
+                },
+                Some(TokenCommand::Attest(token_attest_args)) => {
+                    cli_token_attest(token_attest_args, &app_path);
+                },
+                None => {
+                    println!("No token command specified");
+                }
+            }
+        },
+        Some(CooCommand::Message(message_args)) => {
+            match message_args.message_command {
+                Some(MessageCommand::Publish(message_publish_args)) => {
+                    cli_message_publish(message_publish_args, &app_path);
+                },
+                None => {
+                    println!("No message command specified");
+                }
+            }
+        },
+        Some(CooCommand::Bridge(bridge_args)) => {
+            match bridge_args.bridge_command {
+                Some(BridgeCommand::State(bridge_state_args)) => {
+                    cli_bridge_state(bridge_state_args);
+                },
+                None => {
+                    println!("No bridge command specified");
+                }
+            }
+        },
+        Some(CooCommand::Governor(governor_args)) => {
+            let guardian_url = url::Url::from_str(&governor_args.guardian_url_str).unwrap();
+            match governor_args.governor_command {
+                Some(governor_command) => {
+                    cli_governor(governor_command, &guardian_url);
+                },
+                None => {
+                    println!("No governor command specified");
+                }
+            }
+        },
+        Some(CooCommand::Guardian(guardian_args)) => {
+            match guardian_args.guardian_command {
+                Some(GuardianCommand::Health(args)) => {
+                    cli_guardian_health(args);
+                },
+                None => {
+                    println!("No guardian command specified");
+                }
+            }
+        },
         None => {
             println!("No command specified");
         }
@@ -143,98 +1011,282 @@ fn cli_vaa_query(vaa_query_args: VaaQueryArgs, app_path: &Path) {
     let chain = vaa_query_args.chain_id;
     let emitter = vaa_query_args.emitter;
 
-    let vaa_bytes = query_guardian(chain, emitter, sequence, guardian_url).unwrap();
-    // save vaa_bytes to a file in cache
     let emitter_address = resolve_emitter_address(chain, emitter).unwrap();
+    if !vaa_query_args.compare.is_empty() {
+        cli_vaa_compare(&vaa_query_args, &emitter_address, app_path);
+        return;
+    }
+    let vaa_bytes = match query_guardian(chain, emitter, sequence, guardian_url.clone()) {
+        Ok(vaa_bytes) => vaa_bytes,
+        Err(CooError::VaaNotFound(message)) => {
+            println!("the guardian does not have the VAA: {}", message);
+            // work out whether the VAA is late, held back or asked for with the wrong ids
+            let diagnosis = diagnose_missing_vaa(&guardian_url, chain, &emitter_address, sequence);
+            println!("{}", pretty_not_found_diagnosis(&diagnosis));
+            if let Some(entry) = &diagnosis.enqueued {
+                println!("{}", pretty_enqueued_vaa(entry, current_timestamp() as u64));
+            }
+            std::process::exit(1);
+        },
+        Err(e) => {
+            println!("failed to query the VAA: {:?}", e);
+            std::process::exit(1);
+        },
+    };
+    // save vaa_bytes to a file in cache
+    let cache_path = save_vaa(app_path, chain, &emitter_address, sequence, &vaa_bytes);
+    println!("saved {} bytes to {:?}", vaa_bytes.len(), cache_path);
+    println!("vaa data: {}", hex::encode(&vaa_bytes));
+}
+
+fn cli_vaa_compare(vaa_query_args: &VaaQueryArgs, emitter_address: &str, app_path: &Path) {
+    let guardian_urls: Vec<url::Url> = vaa_query_args.compare.iter().map(|u| url::Url::from_str(u).unwrap()).collect();
+    let (chain, sequence) = (vaa_query_args.chain_id, vaa_query_args.sequence);
+    let copies = query_guardians(chain, vaa_query_args.emitter, sequence, &guardian_urls);
+    // the signatures are checked against the guardian set of the first VAA that was returned
+    let guardian_set_index = copies.iter()
+        .filter_map(|c| c.vaa_bytes.as_ref().ok())
+        .find_map(|vaa_bytes| parse_vaa(vaa_bytes).ok())
+        .map(|vaa| vaa.guardian_set_index);
+    let guardians = match guardian_set_index {
+        Some(index) => guardian_set(index).unwrap_or_else(|e| {
+            println!("failed to read guardian set {}: {:?}", index, e);
+            None
+        }),
+        None => None,
+    };
+    let summaries = summarize_copies(&copies, guardians.as_deref());
+    println!("{}", pretty_vaa_comparison(&summaries, guardians.as_deref()));
+    if !bodies_match(&summaries) {
+        println!("the guardians returned different bodies for the same message");
+        std::process::exit(1);
+    }
+    println!("all bodies match");
+    if vaa_query_args.merge {
+        // without the keys any recoverable signature would be merged, which makes for a VAA that fails to verify
+        let guardians = match &guardians {
+            Some(guardians) => guardians,
+            None => {
+                println!("refusing to merge, the signatures can not be checked without the keys of the guardian set");
+                std::process::exit(1);
+            },
+        };
+        let vaa_bytes = merge_signatures(&copies, guardians).unwrap();
+        let signatures = parse_vaa(&vaa_bytes).unwrap().signatures.len();
+        println!("merged {} signatures, quorum is {}", signatures, quorum(guardians.len()));
+        let cache_path = save_vaa(app_path, chain, emitter_address, sequence, &vaa_bytes);
+        println!("saved {} bytes to {:?}", vaa_bytes.len(), cache_path);
+        println!("vaa data: {}", hex::encode(&vaa_bytes));
+    }
+}
+
+fn save_vaa(app_path: &Path, chain: CooChain, emitter_address: &str, sequence: u64, vaa_bytes: &[u8]) -> PathBuf {
     let vaa_filename = format!("{}-{}-{}.vaa", u16::from(chain), emitter_address, sequence);
     let cache_path = app_path.join("cache").join(vaa_filename);
     let mut file = std::fs::File::create(&cache_path).unwrap();    
-    file.write_all(&vaa_bytes).unwrap();
-    println!("saved {} bytes to {:?}", vaa_bytes.len(), cache_path);
-    println!("vaa data: {}", hex::encode(&vaa_bytes));
+    file.write_all(vaa_bytes).unwrap();
+    cache_path
 }
 
-fn cli_vaa_decode(vaa_decode_args: VaaDecodeArgs, app_path: &Path) {
-    let data_format = vaa_decode_args.data_format;
-    let data = vaa_decode_args.data;
-    let vaa_bytes = match data_format {
+fn output_vaa(vaa_bytes: &[u8], output_format: VaaDataFormat, app_path: &Path, chain: CooChain, emitter_address: &str, sequence: u64) {
+    match output_format {
+        VaaDataFormat::Base64 => println!("{}", bytestobase64(vaa_bytes)),
+        VaaDataFormat::Base58 => println!("{}", bytestobase58(vaa_bytes)),
+        VaaDataFormat::Hex => println!("{}", hex::encode(vaa_bytes)),
+        VaaDataFormat::Path => {
+            // saved with the same naming as queried VAAs so that list and decode can find it
+            let cache_path = save_vaa(app_path, chain, emitter_address, sequence, vaa_bytes);
+            println!("saved {} bytes to {:?}", vaa_bytes.len(), cache_path);
+        },
+    }
+}
+
+fn current_timestamp() -> u32 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as u32
+}
+
+fn build_header(header_args: &VaaHeaderArgs) -> CooVaaHeader {
+    let emitter_address = resolve_emitter_address(header_args.chain_id, header_args.emitter).unwrap();
+    CooVaaHeader {
+        chain: header_args.chain_id,
+        emitter_address: hextoaddress(&emitter_address).unwrap(),
+        sequence: header_args.sequence,
+        nonce: header_args.nonce,
+        timestamp: header_args.timestamp.unwrap_or_else(current_timestamp),
+        consistency_level: header_args.consistency_level,
+        guardian_set_index: header_args.guardian_set_index,
+    }
+}
+
+fn build_payload(payload: VaaBuildPayload) -> Vec<u8> {
+    match payload {
+        VaaBuildPayload::Raw { payload } => hextobytes(&payload).unwrap(),
+        VaaBuildPayload::TokenTransfer(args) => {
+            let message = wormhole_sdk::token::Message::Transfer {
+                amount: stringtoamount(&args.amount).unwrap(),
+                token_address: hextoaddress(&args.token_address).unwrap(),
+                token_chain: args.token_chain.into(),
+                recipient: hextoaddress(&args.recipient).unwrap(),
+                recipient_chain: args.recipient_chain.into(),
+                fee: stringtoamount(&args.fee).unwrap(),
+            };
+            encode_token_payload(&message).unwrap()
+        },
+        VaaBuildPayload::TokenTransferPayload(args) => {
+            let inner_payload = hextobytes(&args.payload).unwrap();
+            let message = wormhole_sdk::token::Message::TransferWithPayload {
+                amount: stringtoamount(&args.amount).unwrap(),
+                token_address: hextoaddress(&args.token_address).unwrap(),
+                token_chain: args.token_chain.into(),
+                recipient: hextoaddress(&args.recipient).unwrap(),
+                recipient_chain: args.recipient_chain.into(),
+                sender_address: hextoaddress(&args.sender_address).unwrap(),
+                payload: RawMessage::new(&inner_payload),
+            };
+            encode_token_payload(&message).unwrap()
+        },
+        VaaBuildPayload::AssetMeta(args) => {
+            let message = wormhole_sdk::token::Message::AssetMeta {
+                token_address: hextoaddress(&args.token_address).unwrap(),
+                token_chain: args.token_chain.into(),
+                decimals: args.decimals,
+                symbol: args.symbol.parse().expect("symbol longer than 32 bytes"),
+                name: args.name.parse().expect("name longer than 32 bytes"),
+            };
+            encode_token_payload(&message).unwrap()
+        },
+        VaaBuildPayload::NftTransfer(args) => {
+            let message = wormhole_sdk::nft::Message::Transfer {
+                nft_address: hextoaddress(&args.nft_address).unwrap(),
+                nft_chain: args.nft_chain.into(),
+                symbol: args.symbol.parse().expect("symbol longer than 32 bytes"),
+                name: args.name.parse().expect("name longer than 32 bytes"),
+                token_id: wormhole_sdk::nft::TokenId(stringtoamount(&args.token_id).unwrap().0),
+                uri: args.uri.parse().expect("uri too long"),
+                to: hextoaddress(&args.to).unwrap(),
+                to_chain: args.to_chain.into(),
+            };
+            encode_nft_payload(&message).unwrap()
+        },
+        VaaBuildPayload::Governance(args) => {
+            let body = hextobytes(&args.body).unwrap();
+            encode_governance_payload(&args.module, args.action, args.target_chain, &body).unwrap()
+        },
+    }
+}
+
+fn cli_vaa_build(vaa_build_args: VaaBuildArgs, app_path: &Path) {
+    let header = build_header(&vaa_build_args.header);
+    let keys = parse_guardian_keys(&vaa_build_args.header.guardian_keys).unwrap();
+    let payload = build_payload(vaa_build_args.payload);
+
+    let mut vaa = build_vaa(&header, &payload);
+    sign_vaa(&mut vaa, &keys).unwrap();
+    let vaa_bytes = serde_wormhole::to_vec(&vaa).unwrap();
+
+    let emitter_address = hex::encode(header.emitter_address.0);
+    output_vaa(&vaa_bytes, vaa_build_args.output_format, app_path, header.chain, &emitter_address, header.sequence);
+}
+
+fn cli_vaa_edit(vaa_edit_args: VaaEditArgs, app_path: &Path) {
+    let vaa_bytes = read_vaa_bytes(vaa_edit_args.data_format, &vaa_edit_args.data, app_path);
+    let mut vaa = parse_vaa(&vaa_bytes).unwrap();
+
+    let edits: Vec<(&str, &str)> = vaa_edit_args.set.iter().map(|s| {
+        s.split_once('=').unwrap_or_else(|| panic!("expected key=value, got {}", s))
+    }).collect();
+
+    // the payload type is inferred from the original VAA, before any of the header edits
+    let payload_type = match vaa_edit_args.payload_type {
+        PayloadType::SmartInfer => infer_payload_type(&vaa),
+        v => v,
+    };
+
+    let mut payload_edits = Vec::new();
+    for (key, value) in edits {
+        if !edit_vaa_header(&mut vaa, key, value).unwrap() {
+            payload_edits.push((key, value));
+        }
+    }
+    let payload = edit_vaa_payload(payload_type, vaa.payload, &payload_edits).unwrap();
+    vaa.payload = RawMessage::new(&payload);
+
+    match vaa_edit_args.signatures {
+        SignatureMode::Drop => vaa.signatures.clear(),
+        SignatureMode::Keep => {},
+        SignatureMode::Resign => {
+            let keys = parse_guardian_keys(&vaa_edit_args.guardian_keys).unwrap();
+            sign_vaa(&mut vaa, &keys).unwrap();
+        },
+    }
+    let new_vaa_bytes = serde_wormhole::to_vec(&vaa).unwrap();
+    println!("{}", pretty_vaa(&vaa));
+
+    let emitter_address = hex::encode(vaa.emitter_address.0);
+    output_vaa(&new_vaa_bytes, vaa_edit_args.output_format, app_path, vaa.emitter_chain.into(), &emitter_address, vaa.sequence);
+}
+
+fn read_vaa_bytes(data_format: VaaDataFormat, data: &str, app_path: &Path) -> Vec<u8> {
+    match data_format {
         VaaDataFormat::Base64 => {
-            base64tobytes(&data).unwrap()
+            base64tobytes(data).unwrap()
         },
         VaaDataFormat::Base58 => {
-            base58tobytes(&data).unwrap()
+            base58tobytes(data).unwrap()
         },
         VaaDataFormat::Hex => {
-            hextobytes(&data).unwrap()
+            hextobytes(data).unwrap()
         },
         VaaDataFormat::Path => {
             // checks if data is an absolute path
-            let path = if Path::new(&data).is_absolute() {
-                PathBuf::from(&data)
+            let path = if Path::new(data).is_absolute() {
+                PathBuf::from(data)
             } else {
-                app_path.join("cache").join(&data)
+                app_path.join("cache").join(data)
             };
             std::fs::read(&path).unwrap()
         },
+    }
+}
+
+fn cli_vaa_decode(vaa_decode_args: VaaDecodeArgs, app_path: &Path) {
+    let data_format = vaa_decode_args.data_format;
+    let data = vaa_decode_args.data;
+    let vaa_bytes = read_vaa_bytes(data_format, &data, app_path);
+    let vaa = if vaa_decode_args.solana_account {
+        let account = parse_posted_account(&vaa_bytes).unwrap();
+        println!("{}", pretty_posted_account(&account));
+        account.to_vaa(vaa_decode_args.guardian_set_index)
+    } else {
+        parse_vaa(&vaa_bytes).unwrap()
     };
-    let vaa = parse_vaa(&vaa_bytes).unwrap();
     println!("{}", pretty_vaa(&vaa));
     // we'll deal with the payload here
     let payload = vaa.payload;
 
     // if its SmartInfer, we'll perform the inference first before doing the decoding
     let payload_type = match vaa_decode_args.payload_type {
-        PayloadType::SmartInfer => {
-            // we'll first check out what is the emitter address, and from there we will know if it is one of the known contracts
-            // if it is, we'll decode the payload accordingly
-
-            let emitter_address = vaa.emitter_address.to_string().to_lowercase();
-            // emitter_address is a 0 left-padded hex string in lower case.
-            // we'll perform the needed transformation from the map
-            let key = EMITTERS.iter().find_map(|(k, v)| { 
-                    let map_entry = format!("{:0>64}", v).to_lowercase();
-                    if emitter_address == map_entry {
-                        Some(k)
-                    } else {
-                        None
-                    }
-            });
-            match key {
-                Some((_, emitter)) => {
-                    match emitter {
-                        EmitterType::Unset => unreachable!("unset should not be in the map"),
-                        EmitterType::Address(_) => unreachable!("address should not be in the map"),
-                        EmitterType::TokenBridge => {
-                            // we'll check the payload type from the first byte
-                            let payload_type = payload[0];
-                            match payload_type {
-                                0x01 => PayloadType::WormholeTokenTransfer,
-                                0x02 => PayloadType::WormholeAssetMeta,
-                                0x03 => PayloadType::WormholeTokenTransferPayload,
-                                // we're not really sure what this is, so raw bytes it shall be.
-                                _ => PayloadType::RawBytes
-                            }
-                        },
-                        EmitterType::NftBridge => {
-                            // we'll check the payload type from the first byte
-                            let payload_type = payload[0];
-                            match payload_type {
-                                0x01 => PayloadType::WormholeNftTransfer,
-                                // we're not really sure what this is, so raw bytes it shall be.
-                                _ => PayloadType::RawBytes,
-                            }
-                        }
-                        // currently corebridge have governance stuff, so we'll just leave it as raw bytes
-                        EmitterType::CoreBridge => PayloadType::RawBytes, 
-                    }
-                },
-                // not one of the known emitters, so raw bytes it shall be.
-                None => PayloadType::RawBytes,
-            }
-        },
+        PayloadType::SmartInfer => infer_payload_type(&vaa),
         v => v,
     };
 
+    // emitters that the tool doesn't know about may still have a user defined schema or plugin
+    let (schemas, schema_errors) = load_schemas(app_path);
+    for e in schema_errors {
+        println!("warning: skipping schema: {:?}", e);
+    }
+    let schema = find_schema(&schemas, &vaa);
+    let (plugins, plugin_errors) = load_plugins(app_path);
+    for e in plugin_errors {
+        println!("warning: skipping plugin: {:?}", e);
+    }
+    let plugin = find_plugin(&plugins, &vaa);
+    let payload_type = match (vaa_decode_args.payload_type, payload_type, schema, plugin) {
+        (PayloadType::SmartInfer, PayloadType::RawBytes, Some(_), _) => PayloadType::CustomSchema,
+        (PayloadType::SmartInfer, PayloadType::RawBytes, None, Some(_)) => PayloadType::WasmPlugin,
+        (_, v, _, _) => v,
+    };
+
     let payload = match payload_type {
         PayloadType::SmartInfer => unreachable!("smart infer should have been handled above"),
         PayloadType::RawBytes => {
@@ -252,10 +1304,70 @@ fn cli_vaa_decode(vaa_decode_args: VaaDecodeArgs, app_path: &Path) {
             let message = decode_wormhole_token(&vaa).unwrap();
             PayloadResponse::WormholeTokenTransferPayload(message)
         },
+        PayloadType::WormholeGatewayTransfer => {
+            let message = decode_wormhole_token(&vaa).unwrap();
+            PayloadResponse::WormholeGatewayTransfer(message)
+        },
         PayloadType::WormholeNftTransfer => {
             let message = decode_wormhole_nft(&vaa).unwrap();
             PayloadResponse::WormholeNftTransfer(message)
         },
+        PayloadType::WormholeRelayerDelivery => {
+            let message = decode_relayer_payload(payload).unwrap();
+            PayloadResponse::WormholeRelayerDelivery(message)
+        },
+        PayloadType::WormholeRelayerRedelivery => {
+            let message = decode_relayer_payload(payload).unwrap();
+            PayloadResponse::WormholeRelayerRedelivery(message)
+        },
+        PayloadType::WormholeCctpDeposit => {
+            let message = decode_cctp_payload(payload).unwrap();
+            PayloadResponse::WormholeCctpDeposit(message)
+        },
+        PayloadType::WormholeNttTransceiverMessage => {
+            let message = decode_ntt_payload(payload).unwrap();
+            PayloadResponse::WormholeNttTransceiverMessage(message)
+        },
+        PayloadType::WormholeNttTransceiverInit => {
+            let message = decode_ntt_payload(payload).unwrap();
+            PayloadResponse::WormholeNttTransceiverInit(message)
+        },
+        PayloadType::WormholeNttTransceiverRegistration => {
+            let message = decode_ntt_payload(payload).unwrap();
+            PayloadResponse::WormholeNttTransceiverRegistration(message)
+        },
+        PayloadType::PythBatchPriceAttestation => {
+            let message = decode_pyth_payload(payload).unwrap();
+            PayloadResponse::PythBatchPriceAttestation(message)
+        },
+        PayloadType::PythAccumulatorUpdate => {
+            let message = decode_pyth_payload(payload).unwrap();
+            PayloadResponse::PythAccumulatorUpdate(message)
+        },
+        PayloadType::CustomSchema => {
+            let schema = schema.expect("no schema found for the emitter of this VAA");
+            let message = decode_schema_payload(schema, payload).unwrap();
+            PayloadResponse::CustomSchema(message)
+        },
+        PayloadType::WasmPlugin => {
+            let plugin = plugin.expect("no plugin registered for the emitter of this VAA");
+            let message = run_plugin(plugin, &vaa).unwrap();
+            PayloadResponse::WasmPlugin(message)
+        },
+    };
+
+    let wrapped = match &payload {
+        PayloadResponse::WormholeTokenTransfer(wormhole_sdk::token::Message::Transfer { token_address, token_chain, recipient_chain, .. }) |
+        PayloadResponse::WormholeTokenTransferPayload(wormhole_sdk::token::Message::TransferWithPayload { token_address, token_chain, recipient_chain, .. }) |
+        PayloadResponse::WormholeGatewayTransfer(wormhole_sdk::token::Message::TransferWithPayload { token_address, token_chain, recipient_chain, .. })
+            if vaa_decode_args.wrapped => {
+            match wrapped_asset_address(*recipient_chain, *token_chain, token_address) {
+                Ok(Some(address)) => Some(address),
+                Ok(None) => Some("not attested".to_string()),
+                Err(e) => Some(format!("lookup failed: {:?}", e)),
+            }
+        },
+        _ => None,
     };
 
     let display_string = match payload {
@@ -266,15 +1378,395 @@ fn cli_vaa_decode(vaa_decode_args: VaaDecodeArgs, app_path: &Path) {
             format!("{:?}", message)
         },
         PayloadResponse::WormholeTokenTransfer(message) => {
-            pretty_token_payload(&message)
+            pretty_token_payload(&message, wrapped.as_deref())
         },
         PayloadResponse::WormholeTokenTransferPayload(message) => {
-            pretty_token_payload(&message)
+            pretty_token_payload(&message, wrapped.as_deref())
+        },
+        PayloadResponse::WormholeGatewayTransfer(message) => {
+            // the gateway payload itself is rendered as the inner payload of the transfer
+            pretty_token_payload(&message, wrapped.as_deref())
         },
         PayloadResponse::WormholeAssetMeta(message) => {
-            pretty_token_payload(&message)
+            pretty_token_payload(&message, None)
+        },
+        PayloadResponse::WormholeRelayerDelivery(message) |
+        PayloadResponse::WormholeRelayerRedelivery(message) => {
+            // the delivery hash is the hash of the delivery VAA itself
+            pretty_relayer_payload(&message, Some(&vaa_digest(&vaa).unwrap()))
+        },
+        PayloadResponse::WormholeCctpDeposit(message) => {
+            // payloads carried inside the message are rendered underneath it
+            let nested = decode_nested_payloads(&InnerPayload::Cctp(message.clone()));
+            match nested.is_empty() {
+                true => pretty_cctp_payload(&message),
+                false => format!("{}\n{}", pretty_cctp_payload(&message), pretty_inner_payloads(&nested)),
+            }
+        },
+        PayloadResponse::WormholeNttTransceiverMessage(message) |
+        PayloadResponse::WormholeNttTransceiverInit(message) |
+        PayloadResponse::WormholeNttTransceiverRegistration(message) => {
+            // payloads carried inside the message are rendered underneath it
+            let nested = decode_nested_payloads(&InnerPayload::Ntt(message.clone()));
+            match nested.is_empty() {
+                true => pretty_ntt_payload(&message),
+                false => format!("{}\n{}", pretty_ntt_payload(&message), pretty_inner_payloads(&nested)),
+            }
+        },
+        PayloadResponse::PythBatchPriceAttestation(message) |
+        PayloadResponse::PythAccumulatorUpdate(message) => {
+            pretty_pyth_payload(&message)
+        },
+        PayloadResponse::CustomSchema(message) |
+        PayloadResponse::WasmPlugin(message) => {
+            pretty_schema_payload(&message)
         },
     };
 
     println!("{}", display_string);
 }
+
+fn cli_vaa_status(vaa_status_args: VaaStatusArgs, app_path: &Path) {
+    let vaa_bytes = read_vaa_bytes(vaa_status_args.data_format, &vaa_status_args.data, app_path);
+    let vaa = parse_vaa(&vaa_bytes).unwrap();
+    let payload_type = match vaa_status_args.payload_type {
+        PayloadType::SmartInfer => infer_payload_type(&vaa),
+        v => v,
+    };
+    let (target_chain, bridge) = transfer_target(&vaa, payload_type).unwrap();
+    let rpc_url = vaa_status_args.rpc_url.as_deref();
+    let contract = vaa_status_args.contract.as_deref();
+
+    let status = match target_chain {
+        Chain::Solana => {
+            // solana marks a redeemed VAA with a claim account under the redeeming program
+            let program = contract.unwrap_or(match bridge {
+                EmitterType::NftBridge => NFT_BRIDGE_PROGRAM,
+                _ => TOKEN_BRIDGE_PROGRAM,
+            });
+            let claim = claim_address(program, u16::from(vaa.emitter_chain), &vaa.emitter_address, vaa.sequence).unwrap();
+            let rpc_url = rpc_url.unwrap_or(RPC_ENDPOINTS[&CooChain::from(Chain::Solana)]);
+            let redeemed = solana_account_exists(rpc_url, &claim).unwrap();
+            let transaction = if redeemed { solana_first_signature(rpc_url, &claim).unwrap() } else { None };
+            RedemptionStatus { chain: target_chain, contract: program.to_string(), redeemed, transaction }
+        },
+        _ => {
+            let provider = evm_provider(target_chain.into(), rpc_url).unwrap();
+            let contract = evm_contract(target_chain.into(), bridge, contract).unwrap();
+            let digest = vaa_digest(&vaa).unwrap();
+            let vaa_key = (u16::from(vaa.emitter_chain), vaa.emitter_address, vaa.sequence);
+            evm_transfer_status(provider, target_chain, bridge, contract, digest, vaa_key, vaa_status_args.from_block).unwrap()
+        },
+    };
+    println!("{}", pretty_redemption_status(&status));
+}
+
+fn cli_vaa_redeem(vaa_redeem_args: VaaRedeemArgs, app_path: &Path) {
+    let vaa_bytes = read_vaa_bytes(vaa_redeem_args.data_format, &vaa_redeem_args.data, app_path);
+    let vaa = parse_vaa(&vaa_bytes).unwrap();
+    let payload_type = match vaa_redeem_args.payload_type {
+        PayloadType::SmartInfer => infer_payload_type(&vaa),
+        v => v,
+    };
+    let chain = vaa_redeem_args.chain;
+    let contract = vaa_redeem_args.contract.as_deref();
+    let provider = evm_provider(chain, vaa_redeem_args.signer.rpc_url.as_deref()).unwrap();
+
+    // asset meta creates the wrapped token the first time, and updates it afterwards
+    let update_wrapped = match decode_wormhole_token(&vaa) {
+        Ok(wormhole_sdk::token::Message::AssetMeta { token_address, token_chain, .. }) if payload_type == PayloadType::WormholeAssetMeta => {
+            let bridge = evm_contract(chain, EmitterType::TokenBridge, contract).unwrap();
+            evm_wrapped_asset(provider.clone(), bridge, u16::from(token_chain), &token_address).unwrap().is_some()
+        },
+        _ => false,
+    };
+    let call = redeem_call(&vaa, payload_type, &vaa_bytes, vaa_redeem_args.unwrap, update_wrapped).unwrap();
+    let target = evm_contract(chain, call.bridge, contract).unwrap();
+    println!("calling {} on {:?}", call.function, target);
+
+    let wallet = signer_wallet(&vaa_redeem_args.signer);
+    let result = evm_send(provider, wallet, target, call.calldata, 0.into(), vaa_redeem_args.dry_run).unwrap();
+    println!("{}", pretty_send_result(&result));
+    if vaa_redeem_args.dry_run {
+        return;
+    }
+    if let Err(e) = evm_receipt(result) {
+        println!("failed to redeem the VAA: {:?}", e);
+        std::process::exit(1);
+    }
+}
+
+fn cli_vaa_calldata(vaa_calldata_args: VaaCalldataArgs, app_path: &Path) {
+    let vaa_bytes = read_vaa_bytes(vaa_calldata_args.data_format, &vaa_calldata_args.data, app_path);
+    let vaa = parse_vaa(&vaa_bytes).unwrap();
+    let payload_type = match vaa_calldata_args.payload_type {
+        PayloadType::SmartInfer => infer_payload_type(&vaa),
+        v => v,
+    };
+    let call = if vaa_calldata_args.verify {
+        verify_call(&vaa_bytes)
+    } else {
+        redeem_call(&vaa, payload_type, &vaa_bytes, vaa_calldata_args.unwrap, vaa_calldata_args.update_wrapped).unwrap()
+    };
+
+    // transfers and chain specific governance already name the chain they are meant for
+    let chain = match vaa_calldata_args.chain {
+        Some(chain) => chain,
+        None if is_governance_vaa(&vaa) => match decode_governance_header(vaa.payload).unwrap() {
+            (_, _, 0) => {
+                println!("governance VAA applies to every chain, use --chain");
+                std::process::exit(1);
+            },
+            (_, _, chain) => CooChain::from(chain),
+        },
+        None => match transfer_target(&vaa, payload_type) {
+            Ok((chain, _)) => chain.into(),
+            Err(_) => {
+                println!("VAA has no target chain, use --chain");
+                std::process::exit(1);
+            },
+        },
+    };
+    let target = evm_contract(chain, call.bridge, vaa_calldata_args.contract.as_deref()).unwrap();
+    println!("{}", pretty_redeem_call(chain.into(), &target, &call));
+}
+
+fn cli_token_wrapped(token_wrapped_args: TokenWrappedArgs) {
+    let token_chain = Chain::from(token_wrapped_args.origin_chain);
+    let token_address = hextoaddress(&token_wrapped_args.origin_address).unwrap();
+    let wrapped: Vec<_> = token_bridge_chains().into_iter()
+        .map(|chain| (chain, wrapped_asset_address(chain, token_chain, &token_address)))
+        .collect();
+    println!("{}", pretty_wrapped_assets(token_chain, &token_address, &wrapped));
+}
+
+fn signer_wallet(signer: &EvmSignerArgs) -> ethers::signers::LocalWallet {
+    evm_wallet(signer.private_key.as_deref(), signer.keystore.as_deref(), signer.keystore_password.as_deref()).unwrap()
+}
+
+// waits for the guardians to sign a message we just published, then decodes it like `vaa decode`
+fn cli_wait_for_vaa(wait_args: &VaaWaitArgs, chain: CooChain, emitter: EmitterType, sequence: u64, app_path: &Path) {
+    println!("published message {} from {}", sequence, resolve_emitter_address(chain, emitter).unwrap());
+    if wait_args.no_wait {
+        return;
+    }
+    let guardian_url = url::Url::from_str(&wait_args.guardian_url_str).unwrap();
+    let timeout = std::time::Duration::from_secs(wait_args.timeout);
+    let vaa_bytes = wait_for_vaa(chain, emitter, sequence, guardian_url, timeout).unwrap();
+    let emitter_address = resolve_emitter_address(chain, emitter).unwrap();
+    let cache_path = save_vaa(app_path, chain, &emitter_address, sequence, &vaa_bytes);
+    println!("saved {} bytes to {:?}", vaa_bytes.len(), cache_path);
+    cli_vaa_decode(VaaDecodeArgs {
+        data_format: VaaDataFormat::Path,
+        payload_type: PayloadType::SmartInfer,
+        solana_account: false,
+        wrapped: false,
+        data: cache_path.to_string_lossy().to_string(),
+    }, app_path);
+}
+
+fn cli_token_transfer(token_transfer_args: TokenTransferArgs, app_path: &Path) {
+    let chain = token_transfer_args.chain;
+    let provider = evm_provider(chain, token_transfer_args.signer.rpc_url.as_deref()).unwrap();
+    let token_bridge = evm_contract(chain, EmitterType::TokenBridge, token_transfer_args.contract.as_deref()).unwrap();
+    let wallet = signer_wallet(&token_transfer_args.signer);
+    let (core, message_fee) = evm_message_fee(provider.clone(), token_bridge).unwrap();
+
+    let recipient_chain = Chain::from(token_transfer_args.recipient_chain);
+    let call = TransferCall {
+        token: token_transfer_args.token.as_deref().map(|t| hextoevmaddress(t).unwrap().into()),
+        amount: ethers::types::U256::from_big_endian(&stringtoamount(&token_transfer_args.amount).unwrap().0),
+        recipient_chain: u16::from(recipient_chain),
+        recipient: recipienttoaddress(recipient_chain, &token_transfer_args.recipient).unwrap(),
+        fee: ethers::types::U256::from_big_endian(&stringtoamount(&token_transfer_args.fee).unwrap().0),
+        nonce: token_transfer_args.nonce,
+        payload: token_transfer_args.payload.as_deref().map(|p| hextobytes(p).unwrap()),
+    };
+    if let Some(token) = call.token {
+        let result = evm_send(provider.clone(), wallet.clone(), token, approve_calldata(token_bridge, call.amount), 0.into(), false).unwrap();
+        println!("{}", pretty_send_result(&result));
+        // a reverted approval would only make the transfer revert as well
+        if let Err(e) = evm_receipt(result) {
+            println!("failed to approve the token bridge: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+    let (calldata, native) = transfer_calldata(&call);
+    let value = if native { message_fee + call.amount } else { message_fee };
+    let result = evm_send(provider, wallet, token_bridge, calldata, value, false).unwrap();
+    println!("{}", pretty_send_result(&result));
+
+    let sequence = parse_log_message_published(&evm_receipt(result).unwrap(), core, token_bridge).unwrap();
+    cli_wait_for_vaa(&token_transfer_args.wait, chain, EmitterType::Address(ethers::types::H256::from(token_bridge).0), sequence, app_path);
+}
+
+fn cli_token_attest(token_attest_args: TokenAttestArgs, app_path: &Path) {
+    let chain = token_attest_args.chain;
+    let provider = evm_provider(chain, token_attest_args.signer.rpc_url.as_deref()).unwrap();
+    let token_bridge = evm_contract(chain, EmitterType::TokenBridge, token_attest_args.contract.as_deref()).unwrap();
+    let wallet = signer_wallet(&token_attest_args.signer);
+    let (core, message_fee) = evm_message_fee(provider.clone(), token_bridge).unwrap();
+
+    let token = hextoevmaddress(&token_attest_args.token).unwrap().into();
+    let result = evm_send(provider, wallet, token_bridge, attest_calldata(token, token_attest_args.nonce), message_fee, false).unwrap();
+    println!("{}", pretty_send_result(&result));
+
+    let sequence = parse_log_message_published(&evm_receipt(result).unwrap(), core, token_bridge).unwrap();
+    cli_wait_for_vaa(&token_attest_args.wait, chain, EmitterType::Address(ethers::types::H256::from(token_bridge).0), sequence, app_path);
+}
+
+fn cli_message_publish(message_publish_args: MessagePublishArgs, app_path: &Path) {
+    let chain = message_publish_args.chain;
+    let provider = evm_provider(chain, message_publish_args.signer.rpc_url.as_deref()).unwrap();
+    let core = evm_contract(chain, EmitterType::CoreBridge, message_publish_args.contract.as_deref()).unwrap();
+    let wallet = signer_wallet(&message_publish_args.signer);
+    // the signer publishes directly, so it is the emitter of the message
+    let emitter = ethers::signers::Signer::address(&wallet);
+    let message_fee = evm_core_message_fee(provider.clone(), core).unwrap();
+
+    let payload = hextobytes(&message_publish_args.payload).unwrap();
+    let calldata = publish_message_calldata(message_publish_args.nonce, &payload, message_publish_args.consistency);
+    let result = evm_send(provider, wallet, core, calldata, message_fee, false).unwrap();
+    println!("{}", pretty_send_result(&result));
+
+    let sequence = parse_log_message_published(&evm_receipt(result).unwrap(), core, emitter).unwrap();
+    cli_wait_for_vaa(&message_publish_args.wait, chain, EmitterType::Address(ethers::types::H256::from(emitter).0), sequence, app_path);
+}
+
+fn cli_bridge_state(bridge_state_args: BridgeStateArgs) {
+    let chains = if bridge_state_args.chain.is_empty() { core_bridge_chains() } else { bridge_state_args.chain.clone() };
+    // overrides only make sense for a single chain
+    let (rpc_url, contract) = match chains.len() {
+        1 => (bridge_state_args.rpc_url.as_deref(), bridge_state_args.contract.as_deref()),
+        _ => (None, None),
+    };
+    let mut states = Vec::new();
+    for chain in chains {
+        let state = evm_provider(chain, rpc_url)
+            .and_then(|provider| Ok((provider, evm_contract(chain, EmitterType::CoreBridge, contract)?)))
+            .and_then(|(provider, core)| evm_core_state(provider, core));
+        match state {
+            Ok(state) => {
+                println!("{}", pretty_bridge_state(chain, &state));
+                states.push((chain, state));
+            },
+            Err(e) => println!("failed to read the core bridge on {}: {:?}", Chain::from(chain), e),
+        }
+    }
+    println!("{}", pretty_bridge_comparison(&compare_bridge_states(&states)));
+}
+
+fn cli_governor(governor_command: GovernorCommand, guardian_url: &url::Url) {
+    match governor_command {
+        GovernorCommand::AvailableNotional => {
+            println!("{}", pretty_available_notional(&available_notional_by_chain(guardian_url).unwrap()));
+        },
+        GovernorCommand::EnqueuedVaas => {
+            println!("{}", pretty_enqueued_vaas(&enqueued_vaas(guardian_url).unwrap()));
+        },
+        GovernorCommand::TokenList => {
+            println!("{}", pretty_token_list(&token_list(guardian_url).unwrap()));
+        },
+        GovernorCommand::IsVaaEnqueued(args) => {
+            let emitter_address = resolve_emitter_address(args.chain_id, args.emitter).unwrap();
+            let enqueued = is_vaa_enqueued(guardian_url, args.chain_id, &emitter_address, args.sequence).unwrap();
+            println!("enqueued: {}", enqueued);
+        },
+    }
+}
+
+fn cli_guardian_health(args: GuardianHealthArgs) {
+    let mut heartbeats = Vec::new();
+    for guardian_url_str in &args.guardian_url_str {
+        let guardian_url = url::Url::from_str(guardian_url_str).unwrap();
+        // a node that is down should not hide the heartbeats seen by the others
+        match query_heartbeats(&guardian_url) {
+            Ok(h) => heartbeats.extend(h),
+            Err(e) => println!("failed to query heartbeats from {}: {:?}", guardian_url, e),
+        }
+    }
+    let heartbeats = merge_heartbeats(heartbeats);
+    println!("{}", pretty_heartbeats(&heartbeats, &args.chain, args.max_lag, current_timestamp() as u64));
+}
+
+fn cli_governance(governance_command: GovernanceCommand, app_path: &Path) {
+    let (vaa_args, payload) = match governance_command {
+        GovernanceCommand::RegisterChain(args) => {
+            let emitter_address = hextoaddress(&args.emitter_address).unwrap();
+            let payload = register_chain_payload(args.module, args.emitter_chain, &emitter_address).unwrap();
+            (args.vaa, payload)
+        },
+        GovernanceCommand::ContractUpgrade(args) => {
+            let new_contract = hextoaddress(&args.new_contract).unwrap();
+            let payload = contract_upgrade_payload(args.module, args.target_chain, &new_contract).unwrap();
+            (args.vaa, payload)
+        },
+        GovernanceCommand::GuardianSetUpgrade(args) => {
+            let new_guardian_set_index = match args.new_guardian_set_index.or(args.vaa.guardian_set_index.checked_add(1)) {
+                Some(index) => index,
+                None => {
+                    println!("guardian set index {} can not be incremented, use --new-guardian-set-index", args.vaa.guardian_set_index);
+                    std::process::exit(1);
+                },
+            };
+            let guardians: Vec<[u8; 20]> = args.guardians.iter().map(|g| hextoguardian(g).unwrap()).collect();
+            let payload = guardian_set_upgrade_payload(new_guardian_set_index, &guardians).unwrap();
+            (args.vaa, payload)
+        },
+        GovernanceCommand::SetMessageFee(args) => {
+            let fee = stringtoamount(&args.fee).unwrap();
+            let payload = set_message_fee_payload(args.target_chain, &fee).unwrap();
+            (args.vaa, payload)
+        },
+    };
+
+    let header = CooVaaHeader {
+        chain: GOVERNANCE_CHAIN.into(),
+        emitter_address: GOVERNANCE_EMITTER,
+        sequence: vaa_args.sequence,
+        nonce: vaa_args.nonce,
+        timestamp: vaa_args.timestamp.unwrap_or_else(current_timestamp),
+        consistency_level: vaa_args.consistency_level,
+        guardian_set_index: vaa_args.guardian_set_index,
+    };
+    let keys = parse_guardian_keys(&vaa_args.guardian_keys).unwrap();
+    let mut vaa = build_vaa(&header, &payload);
+    sign_vaa(&mut vaa, &keys).unwrap();
+    let vaa_bytes = serde_wormhole::to_vec(&vaa).unwrap();
+
+    let emitter_address = hex::encode(GOVERNANCE_EMITTER.0);
+    output_vaa(&vaa_bytes, vaa_args.output_format, app_path, header.chain, &emitter_address, header.sequence);
+}
+
+fn cli_address_derive(args: AddressDeriveArgs) {
+    let (seeds, default_program) = match args.account {
+        AddressDeriveAccount::BridgeConfig => (bridge_config_seeds(), CORE_BRIDGE_PROGRAM),
+        AddressDeriveAccount::GuardianSet { index } => (guardian_set_seeds(index), CORE_BRIDGE_PROGRAM),
+        AddressDeriveAccount::FeeCollector => (fee_collector_seeds(), CORE_BRIDGE_PROGRAM),
+        AddressDeriveAccount::Sequence { emitter } => (sequence_seeds(&base58topubkey(&emitter).unwrap()), CORE_BRIDGE_PROGRAM),
+        AddressDeriveAccount::PostedVaa { hash } => {
+            let hash: [u8; 32] = hextobytes(&hash).unwrap().try_into().expect("VAA hash must be 32 bytes");
+            (posted_vaa_seeds(&hash), CORE_BRIDGE_PROGRAM)
+        },
+        AddressDeriveAccount::Claim { emitter_chain, emitter_address, sequence } => {
+            let emitter_address = hextoaddress(&emitter_address).unwrap();
+            (claim_seeds(u16::from(emitter_chain), &emitter_address, sequence), TOKEN_BRIDGE_PROGRAM)
+        },
+        AddressDeriveAccount::Emitter => (emitter_seeds(), TOKEN_BRIDGE_PROGRAM),
+        AddressDeriveAccount::WrappedMint { token_chain, token_address } => {
+            let token_address = hextoaddress(&token_address).unwrap();
+            (wrapped_mint_seeds(u16::from(token_chain), &token_address), TOKEN_BRIDGE_PROGRAM)
+        },
+        AddressDeriveAccount::WrappedMeta { mint } => (wrapped_meta_seeds(&base58topubkey(&mint).unwrap()), TOKEN_BRIDGE_PROGRAM),
+        AddressDeriveAccount::Custody { mint } => (custody_seeds(&base58topubkey(&mint).unwrap()), TOKEN_BRIDGE_PROGRAM),
+    };
+    let program = args.program.unwrap_or_else(|| default_program.to_string());
+    let (address, bump) = derive_address(&seeds, &program).unwrap();
+    println!("{}", pretty_program_address(&program, &address, bump));
+}
+
+fn cli_emitter_derive(args: EmitterDeriveArgs) {
+    let chain = Chain::from(args.chain);
+    let emitter_address = derive_emitter_address(chain, &args.program).unwrap();
+    println!("{}", pretty_emitter_address(chain, &args.program, emitter_derivation(chain), &emitter_address));
+}
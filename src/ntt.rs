@@ -0,0 +1,292 @@
+use comfy_table::{Table, Row};
+use wormhole_sdk::{Address, Chain};
+
+use crate::common::*;
+
+// 4 byte prefixes that the ntt contracts put in front of their messages
+pub const WH_TRANSCEIVER_PAYLOAD_PREFIX: [u8; 4] = [0x99, 0x45, 0xff, 0x10];
+pub const WH_TRANSCEIVER_INIT_PREFIX: [u8; 4] = [0x9c, 0x23, 0xbd, 0x3b];
+pub const WH_TRANSCEIVER_REGISTRATION_PREFIX: [u8; 4] = [0x18, 0xfc, 0x67, 0xc2];
+pub const NATIVE_TOKEN_TRANSFER_PREFIX: [u8; 4] = [0x99, 0x4e, 0x54, 0x54];
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NativeTokenTransfer {
+    pub decimals: u8,
+    pub amount: u64,
+    pub source_token: Address,
+    pub to: Address,
+    pub to_chain: u16,
+    pub additional_payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NttManagerPayload {
+    NativeTokenTransfer(NativeTokenTransfer),
+    Unknown(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NttManagerMessage {
+    pub id: [u8; 32],
+    pub sender: Address,
+    pub payload: NttManagerPayload,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TransceiverMessage {
+    pub source_ntt_manager: Address,
+    pub recipient_ntt_manager: Address,
+    pub ntt_manager_message: NttManagerMessage,
+    pub transceiver_payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NttMessage {
+    Transceiver(TransceiverMessage),
+    TransceiverInit { ntt_manager: Address, ntt_manager_mode: u8, token: Address, token_decimals: u8 },
+    TransceiverRegistration { transceiver_chain: u16, transceiver_address: Address },
+}
+
+fn read_u16_prefixed<'a>(reader: &mut PayloadReader<'a>) -> Result<&'a [u8], CooError> {
+    let len = reader.read_u16()? as usize;
+    reader.read_bytes(len)
+}
+
+fn decode_native_token_transfer(payload: &[u8]) -> Result<NativeTokenTransfer, CooError> {
+    let mut reader = PayloadReader::new(payload);
+    let prefix: [u8; 4] = reader.read_array()?;
+    if prefix != NATIVE_TOKEN_TRANSFER_PREFIX {
+        return Err(CooError::ParseError(format!("not a native token transfer: {}", hex::encode(prefix))));
+    }
+    let decimals = reader.read_u8()?;
+    let amount = reader.read_u64()?;
+    let source_token = reader.read_address()?;
+    let to = reader.read_address()?;
+    let to_chain = reader.read_u16()?;
+    // older managers do not append the additional payload at all
+    let additional_payload = if reader.is_empty() {
+        Vec::new()
+    } else {
+        read_u16_prefixed(&mut reader)?.to_vec()
+    };
+    if !reader.is_empty() {
+        return Err(CooError::ParseError("trailing bytes after native token transfer".to_string()));
+    }
+    Ok(NativeTokenTransfer { decimals, amount, source_token, to, to_chain, additional_payload })
+}
+
+fn decode_ntt_manager_message(payload: &[u8]) -> Result<NttManagerMessage, CooError> {
+    let mut reader = PayloadReader::new(payload);
+    let id = reader.read_array()?;
+    let sender = reader.read_address()?;
+    let inner = read_u16_prefixed(&mut reader)?;
+    let payload = if inner.starts_with(&NATIVE_TOKEN_TRANSFER_PREFIX) {
+        NttManagerPayload::NativeTokenTransfer(decode_native_token_transfer(inner)?)
+    } else {
+        NttManagerPayload::Unknown(inner.to_vec())
+    };
+    if !reader.is_empty() {
+        return Err(CooError::ParseError("trailing bytes after ntt manager message".to_string()));
+    }
+    Ok(NttManagerMessage { id, sender, payload })
+}
+
+pub fn decode_ntt_payload(payload: &[u8]) -> Result<NttMessage, CooError> {
+    let mut reader = PayloadReader::new(payload);
+    let prefix: [u8; 4] = reader.read_array()?;
+    let message = match prefix {
+        WH_TRANSCEIVER_PAYLOAD_PREFIX => {
+            let source_ntt_manager = reader.read_address()?;
+            let recipient_ntt_manager = reader.read_address()?;
+            let ntt_manager_message = decode_ntt_manager_message(read_u16_prefixed(&mut reader)?)?;
+            let transceiver_payload = read_u16_prefixed(&mut reader)?.to_vec();
+            NttMessage::Transceiver(TransceiverMessage { source_ntt_manager, recipient_ntt_manager, ntt_manager_message, transceiver_payload })
+        },
+        WH_TRANSCEIVER_INIT_PREFIX => {
+            NttMessage::TransceiverInit {
+                ntt_manager: reader.read_address()?,
+                ntt_manager_mode: reader.read_u8()?,
+                token: reader.read_address()?,
+                token_decimals: reader.read_u8()?,
+            }
+        },
+        WH_TRANSCEIVER_REGISTRATION_PREFIX => {
+            NttMessage::TransceiverRegistration {
+                transceiver_chain: reader.read_u16()?,
+                transceiver_address: reader.read_address()?,
+            }
+        },
+        _ => return Err(CooError::ParseError(format!("unknown ntt prefix: {}", hex::encode(prefix)))),
+    };
+    if !reader.is_empty() {
+        return Err(CooError::ParseError("trailing bytes after ntt payload".to_string()));
+    }
+    return Ok(message);
+}
+
+fn ntt_manager_mode_name(mode: u8) -> &'static str {
+    match mode {
+        0 => "Locking",
+        1 => "Burning",
+        _ => "Unknown",
+    }
+}
+
+pub fn pretty_ntt_payload(message: &NttMessage) -> String {
+    let mut table = Table::new();
+    table.set_header(["Wormhole NTT Payload Information"]);
+    let rows: Vec<Row> = match message {
+        NttMessage::Transceiver(transceiver_message) => {
+            let manager_message = &transceiver_message.ntt_manager_message;
+            let mut rows: Vec<Row> = vec![
+                ["Payload Type", "WormholeTransceiverMessage"].into(),
+                ["Source NTT Manager", &transceiver_message.source_ntt_manager.to_string()].into(),
+                ["Recipient NTT Manager", &transceiver_message.recipient_ntt_manager.to_string()].into(),
+                ["Message ID", &hex::encode(manager_message.id)].into(),
+                ["Sender", &manager_message.sender.to_string()].into(),
+            ];
+            match &manager_message.payload {
+                NttManagerPayload::NativeTokenTransfer(transfer) => {
+                    let transfer_rows: Vec<Row> = vec![
+                        ["Amount", &trimmedamounttostring(transfer.amount, transfer.decimals)].into(),
+                        ["Decimals (Trimmed)", &transfer.decimals.to_string()].into(),
+                        ["Source Token", &transfer.source_token.to_string()].into(),
+                        ["Recipient", &transfer.to.to_string()].into(),
+                        ["Recipient Chain", &Chain::from(transfer.to_chain).to_string()].into(),
+                        ["Additional Payload", &hex::encode(&transfer.additional_payload)].into(),
+                    ];
+                    rows.extend(transfer_rows);
+                },
+                NttManagerPayload::Unknown(payload) => {
+                    rows.push(["NTT Manager Payload", &hex::encode(payload)].into());
+                },
+            }
+            rows.push(["Transceiver Payload", &hex::encode(&transceiver_message.transceiver_payload)].into());
+            rows
+        },
+        NttMessage::TransceiverInit { ntt_manager, ntt_manager_mode, token, token_decimals } => {
+            vec![
+                ["Payload Type", "TransceiverInit"].into(),
+                ["NTT Manager", &ntt_manager.to_string()].into(),
+                ["NTT Manager Mode", ntt_manager_mode_name(*ntt_manager_mode)].into(),
+                ["Token", &token.to_string()].into(),
+                ["Token Decimals", &token_decimals.to_string()].into(),
+            ]
+        },
+        NttMessage::TransceiverRegistration { transceiver_chain, transceiver_address } => {
+            vec![
+                ["Payload Type", "TransceiverRegistration"].into(),
+                ["Transceiver Chain", &Chain::from(*transceiver_chain).to_string()].into(),
+                ["Transceiver Address", &transceiver_address.to_string()].into(),
+            ]
+        },
+    };
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_u16_prefixed(out: &mut Vec<u8>, data: &[u8]) {
+        out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        out.extend_from_slice(data);
+    }
+
+    fn encode_native_token_transfer(transfer: &NativeTokenTransfer) -> Vec<u8> {
+        let mut out = NATIVE_TOKEN_TRANSFER_PREFIX.to_vec();
+        out.push(transfer.decimals);
+        out.extend_from_slice(&transfer.amount.to_be_bytes());
+        out.extend_from_slice(&transfer.source_token.0);
+        out.extend_from_slice(&transfer.to.0);
+        out.extend_from_slice(&transfer.to_chain.to_be_bytes());
+        push_u16_prefixed(&mut out, &transfer.additional_payload);
+        out
+    }
+
+    fn encode_transceiver_message(message: &TransceiverMessage, manager_payload: &[u8]) -> Vec<u8> {
+        let mut manager_message = message.ntt_manager_message.id.to_vec();
+        manager_message.extend_from_slice(&message.ntt_manager_message.sender.0);
+        push_u16_prefixed(&mut manager_message, manager_payload);
+        let mut out = WH_TRANSCEIVER_PAYLOAD_PREFIX.to_vec();
+        out.extend_from_slice(&message.source_ntt_manager.0);
+        out.extend_from_slice(&message.recipient_ntt_manager.0);
+        push_u16_prefixed(&mut out, &manager_message);
+        push_u16_prefixed(&mut out, &message.transceiver_payload);
+        out
+    }
+
+    fn with_trailing_byte(payload: &[u8]) -> Vec<u8> {
+        let mut payload = payload.to_vec();
+        payload.push(0);
+        payload
+    }
+
+    #[test]
+    fn test_decode_transceiver_message() {
+        let transfer = NativeTokenTransfer {
+            decimals: 8,
+            amount: 12345678900,
+            source_token: Address([0x11; 32]),
+            to: Address([0x22; 32]),
+            to_chain: 1,
+            additional_payload: vec![0xab, 0xcd],
+        };
+        let message = TransceiverMessage {
+            source_ntt_manager: Address([0x33; 32]),
+            recipient_ntt_manager: Address([0x44; 32]),
+            ntt_manager_message: NttManagerMessage {
+                id: [0x55; 32],
+                sender: Address([0x66; 32]),
+                payload: NttManagerPayload::NativeTokenTransfer(transfer.clone()),
+            },
+            transceiver_payload: Vec::new(),
+        };
+        let payload = encode_transceiver_message(&message, &encode_native_token_transfer(&transfer));
+        assert_eq!(decode_ntt_payload(&payload).unwrap(), NttMessage::Transceiver(message.clone()));
+        assert!(decode_ntt_payload(&with_trailing_byte(&payload)).is_err());
+
+        // older managers end the transfer at the recipient chain
+        let legacy = encode_native_token_transfer(&transfer);
+        let legacy = &legacy[..legacy.len() - 4];
+        let decoded = decode_native_token_transfer(legacy).unwrap();
+        assert_eq!(decoded, NativeTokenTransfer { additional_payload: Vec::new(), ..transfer.clone() });
+        assert!(decode_native_token_transfer(&with_trailing_byte(&encode_native_token_transfer(&transfer))).is_err());
+
+        // payloads of other manager implementations are kept as they are
+        let mut unknown = message;
+        unknown.ntt_manager_message.payload = NttManagerPayload::Unknown(vec![1, 2, 3]);
+        let payload = encode_transceiver_message(&unknown, &[1, 2, 3]);
+        assert_eq!(decode_ntt_payload(&payload).unwrap(), NttMessage::Transceiver(unknown));
+    }
+
+    #[test]
+    fn test_decode_transceiver_init() {
+        let mut payload = WH_TRANSCEIVER_INIT_PREFIX.to_vec();
+        payload.extend_from_slice(&[0x11; 32]);
+        payload.push(1);
+        payload.extend_from_slice(&[0x22; 32]);
+        payload.push(18);
+        assert_eq!(decode_ntt_payload(&payload).unwrap(), NttMessage::TransceiverInit {
+            ntt_manager: Address([0x11; 32]),
+            ntt_manager_mode: 1,
+            token: Address([0x22; 32]),
+            token_decimals: 18,
+        });
+        assert!(decode_ntt_payload(&with_trailing_byte(&payload)).is_err());
+    }
+
+    #[test]
+    fn test_decode_transceiver_registration() {
+        let mut payload = WH_TRANSCEIVER_REGISTRATION_PREFIX.to_vec();
+        payload.extend_from_slice(&6u16.to_be_bytes());
+        payload.extend_from_slice(&[0x33; 32]);
+        assert_eq!(decode_ntt_payload(&payload).unwrap(), NttMessage::TransceiverRegistration {
+            transceiver_chain: 6,
+            transceiver_address: Address([0x33; 32]),
+        });
+        assert!(decode_ntt_payload(&with_trailing_byte(&payload)).is_err());
+        assert!(decode_ntt_payload(&[0xde, 0xad, 0xbe, 0xef]).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+use wormhole_sdk::Vaa;
+use serde_wormhole::RawMessage;
+
+use crate::common::*;
+use crate::schema::{SchemaMessage, SchemaValue};
+
+// payload decoders compiled to wasm, registered in <app_path>/config/plugins.json as
+//
+// [{ "chain": "ethereum", "emitter": "0x...", "module": "my_decoder.wasm" }]
+//
+// with modules relative to <app_path>/config/plugins. a plugin has no imports and must export
+//   memory
+//   coo_alloc(len: u32) -> u32
+//   coo_decode(header_ptr: u32, header_len: u32, payload_ptr: u32, payload_len: u32) -> u64
+// where the header is the vaa header as json and the result is (ptr << 32 | len) of a json
+//   { "name": "...", "fields": [{ "name": "...", "value": "..." }, { "name": "...", "fields": [ ... ] }] }
+
+// upper bound on the instructions a plugin may execute for a single payload
+const PLUGIN_FUEL: u64 = 100_000_000;
+// upper bound on the linear memory of a plugin, which also bounds the result we copy out of it
+const PLUGIN_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+struct PluginRegistration {
+    chain: String,
+    emitter: String,
+    module: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+struct PluginVaaHeader {
+    version: u8,
+    guardian_set_index: u32,
+    timestamp: u32,
+    nonce: u32,
+    emitter_chain: u16,
+    emitter_address: String,
+    sequence: u64,
+    consistency_level: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginField {
+    name: String,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    fields: Vec<PluginField>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginOutput {
+    name: String,
+    fields: Vec<PluginField>,
+}
+
+// the registered plugins, and the errors of the registrations that are invalid so that they do not break decoding
+pub fn load_plugins(app_path: &Path) -> (HashMap<(CooChain, String), PathBuf>, Vec<CooError>) {
+    let config_path = app_path.join("config");
+    let registry_path = config_path.join("plugins.json");
+    let (mut plugins, mut errors) = (HashMap::new(), Vec::new());
+    if !registry_path.exists() {
+        return (plugins, errors);
+    }
+    let registrations: Vec<PluginRegistration> = match std::fs::read(&registry_path).map_err(CooError::from)
+        .and_then(|bytes| Ok(serde_json::from_slice(&bytes)?)) {
+        Ok(registrations) => registrations,
+        Err(e) => return (plugins, vec![e]),
+    };
+    for r in registrations {
+        let chain = match stringtochain(&r.chain) {
+            Ok(chain) => chain,
+            Err(e) => { errors.push(e); continue; },
+        };
+        // keyed the same way as known emitters, a 0 left-padded lower case hex address
+        let emitter = format!("{:0>64}", r.emitter.trim_start_matches("0x")).to_lowercase();
+        let module = if r.module.is_absolute() { r.module } else { config_path.join("plugins").join(r.module) };
+        plugins.insert((chain, emitter), module);
+    }
+    return (plugins, errors);
+}
+
+pub fn find_plugin<'a>(plugins: &'a HashMap<(CooChain, String), PathBuf>, vaa: &Vaa<&RawMessage>) -> Option<&'a PathBuf> {
+    let key = (CooChain::from(vaa.emitter_chain), vaa.emitter_address.to_string().to_lowercase());
+    plugins.get(&key)
+}
+
+fn plugin_fields(fields: Vec<PluginField>) -> Vec<(String, SchemaValue)> {
+    fields.into_iter().map(|f| {
+        let value = match f.value {
+            Some(v) => SchemaValue::String(v),
+            None => SchemaValue::Struct(plugin_fields(f.fields)),
+        };
+        (f.name, value)
+    }).collect()
+}
+
+pub fn run_plugin(module_path: &Path, vaa: &Vaa<&RawMessage>) -> Result<SchemaMessage, CooError> {
+    run_plugin_module(&std::fs::read(module_path)?, vaa)
+}
+
+fn run_plugin_module(wasm: &[u8], vaa: &Vaa<&RawMessage>) -> Result<SchemaMessage, CooError> {
+    let header = PluginVaaHeader {
+        version: vaa.version,
+        guardian_set_index: vaa.guardian_set_index,
+        timestamp: vaa.timestamp,
+        nonce: vaa.nonce,
+        emitter_chain: u16::from(vaa.emitter_chain),
+        emitter_address: vaa.emitter_address.to_string(),
+        sequence: vaa.sequence,
+        consistency_level: vaa.consistency_level,
+    };
+    let header = serde_json::to_vec(&header)?;
+    let payload: &[u8] = vaa.payload;
+
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module = Module::new(&engine, wasm)?;
+    let limits = StoreLimitsBuilder::new().memory_size(PLUGIN_MEMORY_LIMIT).memories(1).instances(1).build();
+    let mut store = Store::new(&engine, limits);
+    store.limiter(|limits| limits);
+    store.add_fuel(PLUGIN_FUEL).map_err(wasmi::Error::from)?;
+    // the linker is empty, so the plugin has no way to reach outside of its own memory
+    let linker = <Linker<StoreLimits>>::new(&engine);
+    let instance = linker.instantiate(&mut store, &module)?.start(&mut store)?;
+
+    let memory = instance.get_memory(&store, "memory")
+        .ok_or_else(|| CooError::ParseError("plugin does not export its memory".to_string()))?;
+    let alloc = instance.get_typed_func::<u32, u32>(&store, "coo_alloc")?;
+    let decode = instance.get_typed_func::<(u32, u32, u32, u32), u64>(&store, "coo_decode")?;
+
+    let header_ptr = alloc.call(&mut store, header.len() as u32).map_err(wasmi::Error::from)?;
+    memory.write(&mut store, header_ptr as usize, &header).map_err(wasmi::Error::from)?;
+    let payload_ptr = alloc.call(&mut store, payload.len() as u32).map_err(wasmi::Error::from)?;
+    memory.write(&mut store, payload_ptr as usize, payload).map_err(wasmi::Error::from)?;
+
+    let result = decode.call(&mut store, (header_ptr, header.len() as u32, payload_ptr, payload.len() as u32)).map_err(wasmi::Error::from)?;
+    let (result_ptr, result_len) = ((result >> 32) as usize, (result & 0xffff_ffff) as usize);
+    // the result has to lie within the plugin memory, we do not allocate whatever length the plugin claims
+    let output = match result_ptr.checked_add(result_len) {
+        Some(end) if end <= memory.data(&store).len() => memory.data(&store)[result_ptr..end].to_vec(),
+        _ => return Err(CooError::ParseError(format!("plugin result out of bounds: {} bytes at {}", result_len, result_ptr))),
+    };
+
+    let output: PluginOutput = serde_json::from_slice(&output)?;
+    return Ok(SchemaMessage { name: output.name, fields: plugin_fields(output.fields) });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vaa::{build_vaa, test_vaa_header};
+
+    fn test_vaa() -> Vaa<&'static RawMessage> {
+        build_vaa(&test_vaa_header(), &[])
+    }
+
+    fn plugin(decode_body: &str) -> Vec<u8> {
+        wat::parse_str(format!(r#"(module
+            (memory (export "memory") 1)
+            (data (i32.const 0) "{{\"name\":\"Test\",\"fields\":[]}}")
+            (func (export "coo_alloc") (param i32) (result i32) i32.const 1024)
+            (func (export "coo_decode") (param i32 i32 i32 i32) (result i64) {})
+        )"#, decode_body)).unwrap()
+    }
+
+    #[test]
+    fn test_run_plugin_module() {
+        // (0 << 32) | 27, the json at the start of the memory
+        let message = run_plugin_module(&plugin("i64.const 27"), &test_vaa()).unwrap();
+        assert_eq!(message.name, "Test");
+        // a plugin that never returns runs out of fuel
+        assert!(run_plugin_module(&plugin("(loop $forever br $forever) i64.const 0"), &test_vaa()).is_err());
+        // a result length of 4 GiB is rejected without allocating it
+        assert!(run_plugin_module(&plugin("i64.const 0xffffffff"), &test_vaa()).is_err());
+        // and so is a result that starts past the end of the memory
+        assert!(run_plugin_module(&plugin("i64.const 0x0001000000000001"), &test_vaa()).is_err());
+    }
+}
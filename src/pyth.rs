@@ -0,0 +1,198 @@
+use comfy_table::{Table, Row};
+
+use crate::common::*;
+
+// magic bytes of the legacy pyth2wormhole batch attestation and of the accumulator wormhole message
+pub const P2W_MAGIC: [u8; 4] = *b"P2WH";
+pub const ACCUMULATOR_WORMHOLE_MAGIC: [u8; 4] = *b"AUWV";
+
+const BATCH_PRICE_ATTESTATION: u8 = 2;
+const WORMHOLE_MERKLE_ROOT: u8 = 0;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PriceAttestation {
+    pub product_id: [u8; 32],
+    pub price_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+    pub status: u8,
+    pub num_publishers: u32,
+    pub max_num_publishers: u32,
+    pub attestation_time: i64,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub prev_price: i64,
+    pub prev_conf: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PythMessage {
+    BatchPriceAttestation { major_version: u16, minor_version: u16, attestations: Vec<PriceAttestation> },
+    WormholeMerkleRoot { slot: u64, ring_size: u32, root: [u8; 20] },
+}
+
+fn decode_price_attestation(data: &[u8]) -> Result<PriceAttestation, CooError> {
+    let mut reader = PayloadReader::new(data);
+    Ok(PriceAttestation {
+        product_id: reader.read_array()?,
+        price_id: reader.read_array()?,
+        price: reader.read_i64()?,
+        conf: reader.read_u64()?,
+        expo: reader.read_i32()?,
+        ema_price: reader.read_i64()?,
+        ema_conf: reader.read_u64()?,
+        status: reader.read_u8()?,
+        num_publishers: reader.read_u32()?,
+        max_num_publishers: reader.read_u32()?,
+        attestation_time: reader.read_i64()?,
+        publish_time: reader.read_i64()?,
+        prev_publish_time: reader.read_i64()?,
+        prev_price: reader.read_i64()?,
+        prev_conf: reader.read_u64()?,
+    })
+}
+
+pub fn decode_pyth_payload(payload: &[u8]) -> Result<PythMessage, CooError> {
+    let mut reader = PayloadReader::new(payload);
+    let magic: [u8; 4] = reader.read_array()?;
+    let message = match magic {
+        P2W_MAGIC => {
+            let major_version = reader.read_u16()?;
+            let minor_version = reader.read_u16()?;
+            // the header is length prefixed so that newer minor versions can extend it
+            let header_size = reader.read_u16()? as usize;
+            let header = reader.read_bytes(header_size)?;
+            if header.first() != Some(&BATCH_PRICE_ATTESTATION) {
+                return Err(CooError::ParseError(format!("unknown p2w payload id: {:?}", header.first())));
+            }
+            let num_attestations = reader.read_u16()?;
+            // likewise each attestation is sized so that fields can be appended
+            let attestation_size = reader.read_u16()? as usize;
+            let mut attestations = Vec::with_capacity(num_attestations as usize);
+            for _ in 0..num_attestations {
+                attestations.push(decode_price_attestation(reader.read_bytes(attestation_size)?)?);
+            }
+            PythMessage::BatchPriceAttestation { major_version, minor_version, attestations }
+        },
+        ACCUMULATOR_WORMHOLE_MAGIC => {
+            let update_type = reader.read_u8()?;
+            if update_type != WORMHOLE_MERKLE_ROOT {
+                return Err(CooError::ParseError(format!("unknown accumulator update type: {}", update_type)));
+            }
+            PythMessage::WormholeMerkleRoot {
+                slot: reader.read_u64()?,
+                ring_size: reader.read_u32()?,
+                root: reader.read_array()?,
+            }
+        },
+        _ => return Err(CooError::ParseError(format!("unknown pyth magic: {}", hex::encode(magic)))),
+    };
+    return Ok(message);
+}
+
+// pyth exponents are single digits in practice, anything larger is shown unscaled instead of padded with zeros
+const MAX_PRICE_EXPO: u32 = 32;
+
+fn scaledtostring(negative: bool, value: u64, expo: i32) -> String {
+    let sign = if negative { "-" } else { "" };
+    if expo.unsigned_abs() > MAX_PRICE_EXPO {
+        return format!("{}{}e{}", sign, value, expo);
+    }
+    // pyth prices are price * 10^expo
+    if expo >= 0 {
+        return format!("{}{}{}", sign, value, "0".repeat(expo as usize));
+    }
+    format!("{}{}", sign, trimmedamounttostring(value, expo.unsigned_abs() as u8))
+}
+
+pub fn scaledpricetostring(price: i64, expo: i32) -> String {
+    scaledtostring(price < 0, price.unsigned_abs(), expo)
+}
+
+// the confidence interval is unsigned and may not fit a price
+pub fn scaledconftostring(conf: u64, expo: i32) -> String {
+    scaledtostring(false, conf, expo)
+}
+
+fn price_status_name(status: u8) -> &'static str {
+    match status {
+        0 => "Unknown",
+        1 => "Trading",
+        2 => "Halted",
+        3 => "Auction",
+        _ => "Invalid",
+    }
+}
+
+pub fn pretty_pyth_payload(message: &PythMessage) -> String {
+    match message {
+        PythMessage::BatchPriceAttestation { major_version, minor_version, attestations } => {
+            let mut table = Table::new();
+            table.set_header(["Pyth Batch Price Attestation"]);
+            let rows: Vec<Row> = vec![
+                ["Payload Type", "BatchPriceAttestation"].into(),
+                ["Version", &format!("{}.{}", major_version, minor_version)].into(),
+                ["Attestations", &attestations.len().to_string()].into(),
+            ];
+            table.add_rows(rows);
+
+            let mut prices_table = Table::new();
+            prices_table.set_header(["Price ID", "Price", "Confidence", "Exponent", "EMA Price", "Status", "Publish Time"]);
+            for attestation in attestations {
+                prices_table.add_row([
+                    hex::encode(attestation.price_id),
+                    scaledpricetostring(attestation.price, attestation.expo),
+                    scaledconftostring(attestation.conf, attestation.expo),
+                    attestation.expo.to_string(),
+                    scaledpricetostring(attestation.ema_price, attestation.expo),
+                    price_status_name(attestation.status).to_string(),
+                    attestation.publish_time.to_string(),
+                ]);
+            }
+            return format!("{table}\n{prices_table}");
+        },
+        PythMessage::WormholeMerkleRoot { slot, ring_size, root } => {
+            let mut table = Table::new();
+            table.set_header(["Pyth Accumulator Update"]);
+            let rows: Vec<Row> = vec![
+                ["Payload Type", "WormholeMerkleRoot"].into(),
+                ["Slot", &slot.to_string()].into(),
+                ["Ring Size", &ring_size.to_string()].into(),
+                ["Merkle Root", &hex::encode(root)].into(),
+            ];
+            table.add_rows(rows);
+            return format!("{table}");
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaledpricetostring() {
+        assert_eq!(scaledpricetostring(2612345678, -8), "26.12345678");
+        assert_eq!(scaledpricetostring(-150, -2), "-1.50");
+        assert_eq!(scaledpricetostring(15, 2), "1500");
+        assert_eq!(scaledpricetostring(i64::MIN, -2), "-92233720368547758.08");
+        // exponents out of range are not expanded
+        assert_eq!(scaledpricetostring(15, i32::MAX), "15e2147483647");
+        assert_eq!(scaledpricetostring(-15, -300), "-15e-300");
+        assert_eq!(scaledconftostring(u64::MAX, -2), "184467440737095516.15");
+    }
+
+    #[test]
+    fn test_decode_wormhole_merkle_root() {
+        let mut payload = ACCUMULATOR_WORMHOLE_MAGIC.to_vec();
+        payload.push(WORMHOLE_MERKLE_ROOT);
+        payload.extend_from_slice(&100u64.to_be_bytes());
+        payload.extend_from_slice(&10000u32.to_be_bytes());
+        payload.extend_from_slice(&[0xab; 20]);
+        let message = decode_pyth_payload(&payload).unwrap();
+        assert_eq!(message, PythMessage::WormholeMerkleRoot { slot: 100, ring_size: 10000, root: [0xab; 20] });
+    }
+}
@@ -0,0 +1,310 @@
+use comfy_table::{Table, Row};
+use wormhole_sdk::{Address, Amount, Chain};
+
+use crate::common::*;
+
+// message key types of the wormhole relayer, anything else is carried as opaque bytes
+const VAA_KEY_TYPE: u8 = 1;
+// version of the execution info that evm delivery providers use
+const EVM_EXECUTION_INFO_V1: u8 = 0;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VaaKey {
+    pub chain_id: u16,
+    pub emitter_address: Address,
+    pub sequence: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MessageKey {
+    Vaa(VaaKey),
+    Other { key_type: u8, data: Vec<u8> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExecutionInfo {
+    EvmV1 { gas_limit: Amount, target_chain_refund_per_gas_unused: Amount },
+    Unknown(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DeliveryInstruction {
+    pub target_chain: u16,
+    pub target_address: Address,
+    pub payload: Vec<u8>,
+    pub requested_receiver_value: Amount,
+    pub extra_receiver_value: Amount,
+    pub execution_info: ExecutionInfo,
+    pub refund_chain: u16,
+    pub refund_address: Address,
+    pub refund_delivery_provider: Address,
+    pub source_delivery_provider: Address,
+    pub sender_address: Address,
+    pub message_keys: Vec<MessageKey>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RedeliveryInstruction {
+    pub delivery_vaa_key: VaaKey,
+    pub target_chain: u16,
+    pub new_requested_receiver_value: Amount,
+    pub new_execution_info: ExecutionInfo,
+    pub new_source_delivery_provider: Address,
+    pub new_sender_address: Address,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RelayerMessage {
+    Delivery(DeliveryInstruction),
+    Redelivery(RedeliveryInstruction),
+}
+
+fn read_length_prefixed<'a>(reader: &mut PayloadReader<'a>) -> Result<&'a [u8], CooError> {
+    let len = reader.read_u32()? as usize;
+    reader.read_bytes(len)
+}
+
+fn read_vaa_key(reader: &mut PayloadReader) -> Result<VaaKey, CooError> {
+    Ok(VaaKey {
+        chain_id: reader.read_u16()?,
+        emitter_address: reader.read_address()?,
+        sequence: reader.read_u64()?,
+    })
+}
+
+fn decode_execution_info(data: &[u8]) -> ExecutionInfo {
+    // evm execution info is abi encoded as (uint8 version, uint256 gasLimit, uint256 targetChainRefundPerGasUnused)
+    if data.len() == 96 && data[..31].iter().all(|b| *b == 0) && data[31] == EVM_EXECUTION_INFO_V1 {
+        let mut reader = PayloadReader::new(&data[32..]);
+        if let (Ok(gas_limit), Ok(refund)) = (reader.read_amount(), reader.read_amount()) {
+            return ExecutionInfo::EvmV1 { gas_limit, target_chain_refund_per_gas_unused: refund };
+        }
+    }
+    ExecutionInfo::Unknown(data.to_vec())
+}
+
+pub fn decode_relayer_payload(payload: &[u8]) -> Result<RelayerMessage, CooError> {
+    let mut reader = PayloadReader::new(payload);
+    let message = match reader.read_u8()? {
+        1 => {
+            let target_chain = reader.read_u16()?;
+            let target_address = reader.read_address()?;
+            let payload = read_length_prefixed(&mut reader)?.to_vec();
+            let requested_receiver_value = reader.read_amount()?;
+            let extra_receiver_value = reader.read_amount()?;
+            let execution_info = decode_execution_info(read_length_prefixed(&mut reader)?);
+            let refund_chain = reader.read_u16()?;
+            let refund_address = reader.read_address()?;
+            let refund_delivery_provider = reader.read_address()?;
+            let source_delivery_provider = reader.read_address()?;
+            let sender_address = reader.read_address()?;
+            let num_message_keys = reader.read_u8()?;
+            let mut message_keys = Vec::with_capacity(num_message_keys as usize);
+            for _ in 0..num_message_keys {
+                let key_type = reader.read_u8()?;
+                let message_key = if key_type == VAA_KEY_TYPE {
+                    MessageKey::Vaa(read_vaa_key(&mut reader)?)
+                } else {
+                    MessageKey::Other { key_type, data: read_length_prefixed(&mut reader)?.to_vec() }
+                };
+                message_keys.push(message_key);
+            }
+            RelayerMessage::Delivery(DeliveryInstruction {
+                target_chain,
+                target_address,
+                payload,
+                requested_receiver_value,
+                extra_receiver_value,
+                execution_info,
+                refund_chain,
+                refund_address,
+                refund_delivery_provider,
+                source_delivery_provider,
+                sender_address,
+                message_keys,
+            })
+        },
+        2 => {
+            // the delivery VAA key is encoded with its key type, like the message keys of a delivery
+            let key_type = reader.read_u8()?;
+            if key_type != VAA_KEY_TYPE {
+                return Err(CooError::ParseError(format!("unexpected delivery VAA key type: {}", key_type)));
+            }
+            RelayerMessage::Redelivery(RedeliveryInstruction {
+                delivery_vaa_key: read_vaa_key(&mut reader)?,
+                target_chain: reader.read_u16()?,
+                new_requested_receiver_value: reader.read_amount()?,
+                new_execution_info: decode_execution_info(read_length_prefixed(&mut reader)?),
+                new_source_delivery_provider: reader.read_address()?,
+                new_sender_address: reader.read_address()?,
+            })
+        },
+        v => return Err(CooError::ParseError(format!("unknown wormhole relayer payload id: {}", v))),
+    };
+    if !reader.is_empty() {
+        return Err(CooError::ParseError("trailing bytes after wormhole relayer payload".to_string()));
+    }
+    return Ok(message);
+}
+
+fn pretty_vaa_key(key: &VaaKey) -> String {
+    format!("{} / {} / {}", Chain::from(key.chain_id), key.emitter_address, key.sequence)
+}
+
+fn pretty_execution_info(execution_info: &ExecutionInfo) -> String {
+    match execution_info {
+        ExecutionInfo::EvmV1 { gas_limit, target_chain_refund_per_gas_unused } => {
+            format!("EVM v1\nGas Limit: {}\nRefund Per Unused Gas: {}", amounttodecimal(gas_limit), amounttodecimal(target_chain_refund_per_gas_unused))
+        },
+        ExecutionInfo::Unknown(data) => format!("Unknown: {}", hex::encode(data)),
+    }
+}
+
+// the delivery hash is the digest of the delivery VAA, so it can only be shown when the VAA is at hand
+pub fn pretty_relayer_payload(message: &RelayerMessage, delivery_hash: Option<&[u8; 32]>) -> String {
+    let mut table = Table::new();
+    table.set_header(["Wormhole Relayer Payload Information"]);
+    let rows: Vec<Row> = match message {
+        RelayerMessage::Delivery(instruction) => {
+            let message_keys = instruction.message_keys.iter().map(|k| match k {
+                MessageKey::Vaa(key) => format!("VAA: {}", pretty_vaa_key(key)),
+                MessageKey::Other { key_type, data } => format!("Type {}: {}", key_type, hex::encode(data)),
+            }).collect::<Vec<String>>().join("\n");
+            let mut rows: Vec<Row> = vec![["Payload Type", "DeliveryInstruction"].into()];
+            if let Some(delivery_hash) = delivery_hash {
+                rows.push(["Delivery Hash", &format!("0x{}", hex::encode(delivery_hash))].into());
+            }
+            rows.extend::<Vec<Row>>(vec![
+                ["Target Chain", &Chain::from(instruction.target_chain).to_string()].into(),
+                ["Target Address", &instruction.target_address.to_string()].into(),
+                ["Payload", &hex::encode(&instruction.payload)].into(),
+                ["Requested Receiver Value", &amounttodecimal(&instruction.requested_receiver_value)].into(),
+                ["Extra Receiver Value", &amounttodecimal(&instruction.extra_receiver_value)].into(),
+                ["Execution Info", &pretty_execution_info(&instruction.execution_info)].into(),
+                ["Refund Chain", &Chain::from(instruction.refund_chain).to_string()].into(),
+                ["Refund Address", &instruction.refund_address.to_string()].into(),
+                ["Refund Delivery Provider", &instruction.refund_delivery_provider.to_string()].into(),
+                ["Source Delivery Provider", &instruction.source_delivery_provider.to_string()].into(),
+                ["Sender Address", &instruction.sender_address.to_string()].into(),
+                ["Message Keys", &message_keys].into(),
+            ]);
+            rows
+        },
+        RelayerMessage::Redelivery(instruction) => {
+            vec![
+                ["Payload Type", "RedeliveryInstruction"].into(),
+                ["Delivery VAA", &pretty_vaa_key(&instruction.delivery_vaa_key)].into(),
+                ["Target Chain", &Chain::from(instruction.target_chain).to_string()].into(),
+                ["New Requested Receiver Value", &amounttodecimal(&instruction.new_requested_receiver_value)].into(),
+                ["New Execution Info", &pretty_execution_info(&instruction.new_execution_info)].into(),
+                ["New Source Delivery Provider", &instruction.new_source_delivery_provider.to_string()].into(),
+                ["New Sender Address", &instruction.new_sender_address.to_string()].into(),
+            ]
+        },
+    };
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_delivery_instruction() {
+        let mut payload = vec![1u8];
+        payload.extend_from_slice(&6u16.to_be_bytes());
+        payload.extend_from_slice(&[0x11; 32]);
+        payload.extend_from_slice(&3u32.to_be_bytes());
+        payload.extend_from_slice(&[0xaa, 0xbb, 0xcc]);
+        let mut requested_receiver_value = [0u8; 32];
+        requested_receiver_value[31] = 10;
+        payload.extend_from_slice(&requested_receiver_value);
+        let mut extra_receiver_value = [0u8; 32];
+        extra_receiver_value[31] = 20;
+        payload.extend_from_slice(&extra_receiver_value);
+        let mut execution_info = [0u8; 96];
+        execution_info[61..64].copy_from_slice(&[0x03, 0x0d, 0x40]);
+        execution_info[95] = 1;
+        payload.extend_from_slice(&96u32.to_be_bytes());
+        payload.extend_from_slice(&execution_info);
+        payload.extend_from_slice(&6u16.to_be_bytes());
+        for address in [0x22, 0x33, 0x44, 0x55, 0x66] {
+            payload.extend_from_slice(&[address; 32]);
+        }
+        // a VAA key, then a key type the decoder does not know which is carried length prefixed
+        payload.extend_from_slice(&[2u8, VAA_KEY_TYPE]);
+        payload.extend_from_slice(&2u16.to_be_bytes());
+        payload.extend_from_slice(&[0x77; 32]);
+        payload.extend_from_slice(&9u64.to_be_bytes());
+        payload.push(2);
+        payload.extend_from_slice(&2u32.to_be_bytes());
+        payload.extend_from_slice(&[0x01, 0x02]);
+
+        let mut gas_limit = [0u8; 32];
+        gas_limit[29..32].copy_from_slice(&[0x03, 0x0d, 0x40]);
+        let mut refund = [0u8; 32];
+        refund[31] = 1;
+        let expected = DeliveryInstruction {
+            target_chain: 6,
+            target_address: Address([0x11; 32]),
+            payload: vec![0xaa, 0xbb, 0xcc],
+            requested_receiver_value: Amount(requested_receiver_value),
+            extra_receiver_value: Amount(extra_receiver_value),
+            execution_info: ExecutionInfo::EvmV1 { gas_limit: Amount(gas_limit), target_chain_refund_per_gas_unused: Amount(refund) },
+            refund_chain: 6,
+            refund_address: Address([0x22; 32]),
+            refund_delivery_provider: Address([0x33; 32]),
+            source_delivery_provider: Address([0x44; 32]),
+            sender_address: Address([0x55; 32]),
+            message_keys: vec![
+                MessageKey::Vaa(VaaKey { chain_id: 2, emitter_address: Address([0x77; 32]), sequence: 9 }),
+                MessageKey::Other { key_type: 2, data: vec![0x01, 0x02] },
+            ],
+        };
+        assert_eq!(decode_relayer_payload(&payload).unwrap(), RelayerMessage::Delivery(expected));
+        match decode_relayer_payload(&payload).unwrap() {
+            RelayerMessage::Delivery(instruction) => match instruction.execution_info {
+                ExecutionInfo::EvmV1 { gas_limit, .. } => assert_eq!(amounttodecimal(&gas_limit), "200000"),
+                ExecutionInfo::Unknown(_) => panic!("expected evm execution info"),
+            },
+            RelayerMessage::Redelivery(_) => panic!("expected a delivery instruction"),
+        }
+        payload.push(0);
+        assert!(decode_relayer_payload(&payload).is_err());
+    }
+
+    #[test]
+    fn test_decode_redelivery_instruction() {
+        // abi.encodePacked(uint8(2), uint8(VAA_KEY_TYPE), encodeVaaKey(key), ...)
+        let mut payload = vec![2u8, VAA_KEY_TYPE];
+        payload.extend_from_slice(&2u16.to_be_bytes());
+        payload.extend_from_slice(&[0x11; 32]);
+        payload.extend_from_slice(&7u64.to_be_bytes());
+        payload.extend_from_slice(&6u16.to_be_bytes());
+        payload.extend_from_slice(&[0u8; 32]);
+        let mut execution_info = [0u8; 96];
+        execution_info[63] = 100;
+        payload.extend_from_slice(&96u32.to_be_bytes());
+        payload.extend_from_slice(&execution_info);
+        payload.extend_from_slice(&[0x22; 32]);
+        payload.extend_from_slice(&[0x33; 32]);
+
+        let message = decode_relayer_payload(&payload).unwrap();
+        match message {
+            RelayerMessage::Redelivery(instruction) => {
+                assert_eq!(instruction.delivery_vaa_key.sequence, 7);
+                assert_eq!(instruction.target_chain, 6);
+                match instruction.new_execution_info {
+                    ExecutionInfo::EvmV1 { gas_limit, .. } => assert_eq!(amounttodecimal(&gas_limit), "100"),
+                    ExecutionInfo::Unknown(_) => panic!("expected evm execution info"),
+                }
+            },
+            RelayerMessage::Delivery(_) => panic!("expected a redelivery instruction"),
+        }
+        // a key without its type byte is off by one
+        let mut unkeyed = vec![2u8];
+        unkeyed.extend_from_slice(&payload[2..]);
+        assert!(decode_relayer_payload(&unkeyed).is_err());
+    }
+}
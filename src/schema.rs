@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use comfy_table::{Table, Row};
+use serde::Deserialize;
+use wormhole_sdk::{Address, Chain, Vaa};
+use serde_wormhole::RawMessage;
+
+use crate::common::*;
+
+// user defined payload layouts, loaded from <app_path>/config/schemas/*.json
+//
+// {
+//   "name": "MyMessage",
+//   "emitters": [{ "chain": "ethereum", "address": "0x..." }],
+//   "fields": [
+//     { "name": "payload_id", "type": "u8" },
+//     { "name": "amount", "type": "u256" },
+//     { "name": "recipient_chain", "type": "u16" },
+//     { "name": "recipient", "type": "address", "chain_field": "recipient_chain" },
+//     { "name": "memo", "type": "string", "length_prefix": 2 },
+//     { "name": "action", "type": "enum", "variants": { "1": [ ... ], "2": [ ... ] } },
+//     { "name": "extra", "type": "struct", "fields": [ ... ] }
+//   ]
+// }
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SchemaEmitter {
+    pub chain: String,
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SchemaField {
+    pub name: String,
+    #[serde(flatten)]
+    pub field_type: SchemaFieldType,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SchemaFieldType {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    // 32 byte wormhole address, the chain hint is either a fixed chain or a previously decoded u16 field
+    Address {
+        #[serde(default)]
+        chain: Option<String>,
+        #[serde(default)]
+        chain_field: Option<String>,
+    },
+    // byte length of the length prefix (1, 2 or 4), or a fixed length
+    Bytes {
+        #[serde(default)]
+        length_prefix: Option<u8>,
+        #[serde(default)]
+        length: Option<usize>,
+    },
+    String {
+        #[serde(default)]
+        length_prefix: Option<u8>,
+        #[serde(default)]
+        length: Option<usize>,
+    },
+    // a discriminator byte followed by the fields of the matching variant. the keys are strings because serde
+    // buffers flattened content and cannot hand integer map keys to it, they are checked to be u8 when loading
+    Enum {
+        variants: HashMap<String, Vec<SchemaField>>,
+    },
+    Struct {
+        fields: Vec<SchemaField>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PayloadSchema {
+    pub name: String,
+    pub emitters: Vec<SchemaEmitter>,
+    pub fields: Vec<SchemaField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SchemaValue {
+    Uint(String),
+    Address { address: Address, chain: Option<u16> },
+    Bytes(Vec<u8>),
+    String(String),
+    Enum { discriminator: u8, fields: Vec<(String, SchemaValue)> },
+    Struct(Vec<(String, SchemaValue)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SchemaMessage {
+    pub name: String,
+    pub fields: Vec<(String, SchemaValue)>,
+}
+
+// chain names in schema files are checked when loading, CooChain::from panics on unknown names
+fn parse_schema_chain(chain: &str) -> Result<u16, CooError> {
+    Ok(u16::from(stringtochain(chain)?))
+}
+
+fn validate_fields(fields: &[SchemaField]) -> Result<(), CooError> {
+    for field in fields {
+        match &field.field_type {
+            SchemaFieldType::Address { chain: Some(chain), .. } => { parse_schema_chain(chain)?; },
+            SchemaFieldType::Enum { variants } => {
+                for (discriminator, fields) in variants {
+                    discriminator.parse::<u8>()
+                        .map_err(|_| CooError::ParseError(format!("enum variant of {} is not a u8: {}", field.name, discriminator)))?;
+                    validate_fields(fields)?;
+                }
+            },
+            SchemaFieldType::Struct { fields } => validate_fields(fields)?,
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+fn load_schema(path: &Path) -> Result<PayloadSchema, CooError> {
+    let schema: PayloadSchema = serde_json::from_slice(&std::fs::read(path)?)
+        .map_err(|e| CooError::ParseError(format!("invalid schema {:?}: {}", path, e)))?;
+    for emitter in &schema.emitters {
+        parse_schema_chain(&emitter.chain)?;
+        hextoaddress(&emitter.address)?;
+    }
+    validate_fields(&schema.fields)?;
+    return Ok(schema);
+}
+
+// the schemas that loaded, and the errors of the files that did not so that one bad file does not break decoding
+pub fn load_schemas(app_path: &Path) -> (Vec<PayloadSchema>, Vec<CooError>) {
+    let schema_path = app_path.join("config").join("schemas");
+    let (mut schemas, mut errors) = (Vec::new(), Vec::new());
+    if !schema_path.exists() {
+        return (schemas, errors);
+    }
+    let entries = match std::fs::read_dir(&schema_path) {
+        Ok(entries) => entries,
+        Err(e) => return (schemas, vec![e.into()]),
+    };
+    for entry in entries {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(e) => { errors.push(e.into()); continue; },
+        };
+        if path.extension().map_or(true, |e| e != "json") {
+            continue;
+        }
+        match load_schema(&path) {
+            Ok(schema) => schemas.push(schema),
+            Err(e) => errors.push(e),
+        }
+    }
+    return (schemas, errors);
+}
+
+pub fn find_schema<'a>(schemas: &'a [PayloadSchema], vaa: &Vaa<&RawMessage>) -> Option<&'a PayloadSchema> {
+    let emitter_address = vaa.emitter_address.to_string().to_lowercase();
+    schemas.iter().find(|schema| {
+        schema.emitters.iter().any(|emitter| {
+            let address = hextoaddress(&emitter.address).map(|a| a.to_string().to_lowercase());
+            parse_schema_chain(&emitter.chain).ok() == Some(u16::from(vaa.emitter_chain)) && address.ok().as_ref() == Some(&emitter_address)
+        })
+    })
+}
+
+fn read_sized<'a>(reader: &mut PayloadReader<'a>, length_prefix: Option<u8>, length: Option<usize>) -> Result<&'a [u8], CooError> {
+    let len = match (length_prefix, length) {
+        (_, Some(len)) => len,
+        (Some(1), None) => reader.read_u8()? as usize,
+        (Some(2), None) => reader.read_u16()? as usize,
+        (Some(4), None) => reader.read_u32()? as usize,
+        // without any length information the field takes up the rest of the payload
+        (None, None) => return Ok(reader.read_remaining()),
+        (Some(v), None) => return Err(CooError::ParseError(format!("unsupported length prefix size: {}", v))),
+    };
+    reader.read_bytes(len)
+}
+
+fn decode_fields(reader: &mut PayloadReader, fields: &[SchemaField]) -> Result<Vec<(String, SchemaValue)>, CooError> {
+    let mut values: Vec<(String, SchemaValue)> = Vec::with_capacity(fields.len());
+    for field in fields {
+        let value = match &field.field_type {
+            SchemaFieldType::U8 => SchemaValue::Uint(reader.read_u8()?.to_string()),
+            SchemaFieldType::U16 => SchemaValue::Uint(reader.read_u16()?.to_string()),
+            SchemaFieldType::U32 => SchemaValue::Uint(reader.read_u32()?.to_string()),
+            SchemaFieldType::U64 => SchemaValue::Uint(reader.read_u64()?.to_string()),
+            SchemaFieldType::U128 => SchemaValue::Uint(u128::from_be_bytes(reader.read_array()?).to_string()),
+            SchemaFieldType::U256 => SchemaValue::Uint(amounttodecimal(&reader.read_amount()?)),
+            SchemaFieldType::Address { chain, chain_field } => {
+                let address = reader.read_address()?;
+                let chain = match (chain, chain_field) {
+                    (Some(c), _) => Some(parse_schema_chain(c)?),
+                    (None, Some(f)) => values.iter().find_map(|(name, value)| match value {
+                        SchemaValue::Uint(v) if name == f => v.parse::<u16>().ok(),
+                        _ => None,
+                    }),
+                    (None, None) => None,
+                };
+                SchemaValue::Address { address, chain }
+            },
+            SchemaFieldType::Bytes { length_prefix, length } => SchemaValue::Bytes(read_sized(reader, *length_prefix, *length)?.to_vec()),
+            SchemaFieldType::String { length_prefix, length } => {
+                let bytes = read_sized(reader, *length_prefix, *length)?;
+                // fixed length strings are usually padded with 0s
+                let string = String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string();
+                SchemaValue::String(string)
+            },
+            SchemaFieldType::Enum { variants } => {
+                let discriminator = reader.read_u8()?;
+                let variant = variants.iter().find(|(k, _)| k.parse::<u8>().ok() == Some(discriminator)).map(|(_, v)| v)
+                    .ok_or_else(|| CooError::ParseError(format!("unknown variant {} for {}", discriminator, field.name)))?;
+                SchemaValue::Enum { discriminator, fields: decode_fields(reader, variant)? }
+            },
+            SchemaFieldType::Struct { fields } => SchemaValue::Struct(decode_fields(reader, fields)?),
+        };
+        values.push((field.name.clone(), value));
+    }
+    return Ok(values);
+}
+
+pub fn decode_schema_payload(schema: &PayloadSchema, payload: &[u8]) -> Result<SchemaMessage, CooError> {
+    let mut reader = PayloadReader::new(payload);
+    let fields = decode_fields(&mut reader, &schema.fields)?;
+    if !reader.is_empty() {
+        return Err(CooError::ParseError(format!("trailing bytes after {} payload", schema.name)));
+    }
+    return Ok(SchemaMessage { name: schema.name.clone(), fields });
+}
+
+fn pretty_schema_address(address: &Address, chain: Option<u16>) -> String {
+    match chain.map(Chain::from) {
+        Some(Chain::Solana) => format!("{} (solana)", bytestobase58(&address.0)),
+        Some(c) if address.0[..12].iter().all(|b| *b == 0) => format!("0x{} ({})", hex::encode(&address.0[12..]), c),
+        Some(c) => format!("{} ({})", address, c),
+        None => address.to_string(),
+    }
+}
+
+fn schema_rows(prefix: &str, fields: &[(String, SchemaValue)], rows: &mut Vec<Row>) {
+    // nested structs and enums are flattened into dotted names
+    for (name, value) in fields {
+        let name = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+        match value {
+            SchemaValue::Uint(v) => rows.push([name.as_str(), v].into()),
+            SchemaValue::Address { address, chain } => rows.push([name.as_str(), &pretty_schema_address(address, *chain)].into()),
+            SchemaValue::Bytes(b) => rows.push([name.as_str(), &hex::encode(b)].into()),
+            SchemaValue::String(s) => rows.push([name.as_str(), s].into()),
+            SchemaValue::Enum { discriminator, fields } => {
+                rows.push([name.as_str(), &format!("variant {}", discriminator)].into());
+                schema_rows(&name, fields, rows);
+            },
+            SchemaValue::Struct(fields) => schema_rows(&name, fields, rows),
+        }
+    }
+}
+
+pub fn pretty_schema_payload(message: &SchemaMessage) -> String {
+    let mut table = Table::new();
+    table.set_header([format!("{} Payload Information", message.name)]);
+    let mut rows: Vec<Row> = vec![["Payload Type", message.name.as_str()].into()];
+    schema_rows("", &message.fields, &mut rows);
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_schema_payload() {
+        let schema: PayloadSchema = serde_json::from_str(r#"{
+            "name": "Test",
+            "emitters": [{ "chain": "ethereum", "address": "0x01" }],
+            "fields": [
+                { "name": "id", "type": "u8" },
+                { "name": "to_chain", "type": "u16" },
+                { "name": "to", "type": "address", "chain_field": "to_chain" },
+                { "name": "memo", "type": "string", "length_prefix": 1 },
+                { "name": "action", "type": "enum", "variants": { "7": [{ "name": "value", "type": "u32" }] } }
+            ]
+        }"#).unwrap();
+        let mut payload = vec![1u8, 0, 2];
+        payload.extend_from_slice(&[0u8; 32]);
+        payload.extend_from_slice(&[2, b'h', b'i']);
+        payload.extend_from_slice(&[7, 0, 0, 0, 42]);
+
+        let message = decode_schema_payload(&schema, &payload).unwrap();
+        assert_eq!(message.fields[0], ("id".to_string(), SchemaValue::Uint("1".to_string())));
+        assert_eq!(message.fields[2].1, SchemaValue::Address { address: Address([0u8; 32]), chain: Some(2) });
+        assert_eq!(message.fields[3].1, SchemaValue::String("hi".to_string()));
+        assert_eq!(message.fields[4].1, SchemaValue::Enum {
+            discriminator: 7,
+            fields: vec![("value".to_string(), SchemaValue::Uint("42".to_string()))],
+        });
+    }
+
+    #[test]
+    fn test_load_schemas() {
+        let app_path = std::env::temp_dir().join(format!("coo-test-schemas-{}", std::process::id()));
+        let schema_path = app_path.join("config").join("schemas");
+        std::fs::create_dir_all(&schema_path).unwrap();
+        std::fs::write(schema_path.join("good.json"), r#"{
+            "name": "Good",
+            "emitters": [{ "chain": "ethereum", "address": "0x01" }],
+            "fields": [{ "name": "action", "type": "enum", "variants": { "1": [], "2": [{ "name": "value", "type": "u8" }] } }]
+        }"#).unwrap();
+        std::fs::write(schema_path.join("bad_chain.json"), r#"{
+            "name": "BadChain",
+            "emitters": [{ "chain": "notachain", "address": "0x01" }],
+            "fields": []
+        }"#).unwrap();
+        std::fs::write(schema_path.join("bad_variant.json"), r#"{
+            "name": "BadVariant",
+            "emitters": [{ "chain": "2", "address": "0x01" }],
+            "fields": [{ "name": "action", "type": "enum", "variants": { "256": [] } }]
+        }"#).unwrap();
+
+        let (schemas, errors) = load_schemas(&app_path);
+        std::fs::remove_dir_all(&app_path).unwrap();
+        assert_eq!(schemas.len(), 1);
+        assert_eq!(schemas[0].name, "Good");
+        assert_eq!(errors.len(), 2);
+    }
+}
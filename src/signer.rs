@@ -0,0 +1,99 @@
+use std::str::FromStr;
+
+use ethers::signers::LocalWallet;
+use ethers::types::{H256, U256};
+use ethers::utils::keccak256;
+use serde::Serialize;
+use wormhole_sdk::Vaa;
+use wormhole_sdk::vaa::Signature;
+
+use crate::common::*;
+
+// the single guardian key that the tilt devnet runs with (guardian address 0xbeFA429d57cD18b7F8A4d91A2da9AB4AF05d0FBe)
+pub const DEVNET_GUARDIAN_KEY: &str = "cfb12303a19cde580bb4dd771639b0d26bc68353645571a8cff516ab2ee113a0";
+
+pub fn parse_guardian_key(key: &str) -> Result<LocalWallet, CooError> {
+    // normalize the key so that both 0x prefixed and plain hex are accepted
+    let key_bytes = hextobytes(key)?;
+    let wallet = LocalWallet::from_str(&hex::encode(key_bytes))?;
+    return Ok(wallet);
+}
+
+pub fn parse_guardian_keys(keys: &[String]) -> Result<Vec<LocalWallet>, CooError> {
+    // no keys given means we sign with the devnet guardian
+    if keys.is_empty() {
+        return Ok(vec![parse_guardian_key(DEVNET_GUARDIAN_KEY)?]);
+    }
+    keys.iter().map(|k| parse_guardian_key(k)).collect()
+}
+
+pub fn vaa_body<P: Serialize>(vaa: &Vaa<P>) -> Result<Vec<u8>, CooError> {
+    let vaa_bytes = serde_wormhole::to_vec(vaa)?;
+    // the header is version (1) + guardian set index (4) + number of signatures (1) + signatures (66 each)
+    let header_len = 6 + 66 * vaa.signatures.len();
+    return Ok(vaa_bytes[header_len..].to_vec());
+}
+
+pub fn vaa_digest<P: Serialize>(vaa: &Vaa<P>) -> Result<[u8; 32], CooError> {
+    // guardians sign the double keccak256 hash of the body
+    let body = vaa_body(vaa)?;
+    return Ok(keccak256(keccak256(body)));
+}
+
+pub fn sign_vaa<P: Serialize>(vaa: &mut Vaa<P>, keys: &[LocalWallet]) -> Result<(), CooError> {
+    let digest = vaa_digest(vaa)?;
+    // the keys are given in guardian set order, so the position of the key is its guardian index
+    vaa.signatures = keys.iter().enumerate().map(|(index, key)| {
+        let signature = key.sign_hash(H256::from(digest));
+        let mut raw_signature = [0u8; 65];
+        signature.r.to_big_endian(&mut raw_signature[0..32]);
+        signature.s.to_big_endian(&mut raw_signature[32..64]);
+        // ethers gives us v as 27/28, wormhole expects the bare recovery id
+        raw_signature[64] = (signature.v - 27) as u8;
+        Signature { index: index as u8, signature: raw_signature }
+    }).collect();
+    return Ok(());
+}
+
+// the guardian key that produced a VAA signature
+pub fn recover_guardian(digest: &[u8; 32], signature: &Signature) -> Result<[u8; 20], CooError> {
+    let raw_signature = signature.signature;
+    let signature = ethers::types::Signature {
+        r: U256::from_big_endian(&raw_signature[0..32]),
+        s: U256::from_big_endian(&raw_signature[32..64]),
+        v: raw_signature[64] as u64 + 27,
+    };
+    let recovered = signature.recover(H256::from(*digest))
+        .map_err(|e| CooError::ParseError(format!("invalid signature: {}", e)))?;
+    return Ok(recovered.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vaa::{build_vaa, test_vaa_header};
+    use ethers::types::Address;
+
+    #[test]
+    fn test_vaa_digest() {
+        // the digest that isTransferCompleted is keyed by, keccak256(keccak256(body))
+        let payload = [0xde, 0xad, 0xbe, 0xef];
+        let mut vaa = build_vaa(&test_vaa_header(), &payload);
+        // the header and signatures are not part of the digest
+        vaa.guardian_set_index = 3;
+        vaa.signatures = vec![Signature { index: 0, signature: [0u8; 65] }];
+        assert_eq!(hex::encode(vaa_digest(&vaa).unwrap()), "7ceb191308e551096df2588d5fcabcaab9fe240d0f2215bf3ba66acc573edec6");
+    }
+
+    #[test]
+    fn test_sign_vaa_recovers_devnet_guardian() {
+        let payload = [0xde, 0xad, 0xbe, 0xef];
+        let mut vaa = build_vaa(&test_vaa_header(), &payload);
+        let keys = parse_guardian_keys(&[]).unwrap();
+        sign_vaa(&mut vaa, &keys).unwrap();
+        assert_eq!(vaa.signatures.len(), 1);
+
+        let recovered = recover_guardian(&vaa_digest(&vaa).unwrap(), &vaa.signatures[0]).unwrap();
+        assert_eq!(Address::from(recovered), Address::from_str("0xbeFA429d57cD18b7F8A4d91A2da9AB4AF05d0FBe").unwrap());
+    }
+}
@@ -0,0 +1,283 @@
+use comfy_table::{Table, Row};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use sha2::{Digest, Sha256};
+use wormhole_sdk::{Address, Chain, Vaa};
+use serde_wormhole::RawMessage;
+
+use crate::common::*;
+
+pub const CORE_BRIDGE_PROGRAM: &str = "worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth";
+pub const TOKEN_BRIDGE_PROGRAM: &str = "wormDTUJ6AWPNvk59vGQbDvGJmqbDTdgWgAqcLBCgUb";
+pub const NFT_BRIDGE_PROGRAM: &str = "WnFt12ZrnzZrFZkt2xsNsaNWoQribnuQ5B5FrDbwDhD";
+
+pub fn base58topubkey(s: &str) -> Result<[u8; 32], CooError> {
+    let bytes = base58tobytes(s)?;
+    bytes.try_into().map_err(|_| CooError::ParseError(format!("solana address must be 32 bytes: {}", s)))
+}
+
+pub fn create_program_address(seeds: &[&[u8]], program_id: &[u8; 32]) -> Option<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    for seed in seeds {
+        hasher.update(seed);
+    }
+    hasher.update(program_id);
+    hasher.update(b"ProgramDerivedAddress");
+    let hash: [u8; 32] = hasher.finalize().into();
+    // a program derived address must not be a valid ed25519 public key
+    if CompressedEdwardsY(hash).decompress().is_some() {
+        return None;
+    }
+    Some(hash)
+}
+
+pub fn find_program_address(seeds: &[&[u8]], program_id: &[u8; 32]) -> Result<([u8; 32], u8), CooError> {
+    // same search as solana's Pubkey::find_program_address, starting from the highest bump
+    for bump in (0..=u8::MAX).rev() {
+        let mut seeds_with_bump = seeds.to_vec();
+        let bump_seed = [bump];
+        seeds_with_bump.push(&bump_seed);
+        if let Some(address) = create_program_address(&seeds_with_bump, program_id) {
+            return Ok((address, bump));
+        }
+    }
+    Err(CooError::ParseError("unable to find a viable program address bump".to_string()))
+}
+
+pub fn bridge_config_seeds() -> Vec<Vec<u8>> {
+    vec![b"Bridge".to_vec()]
+}
+
+pub fn guardian_set_seeds(index: u32) -> Vec<Vec<u8>> {
+    vec![b"GuardianSet".to_vec(), index.to_be_bytes().to_vec()]
+}
+
+pub fn fee_collector_seeds() -> Vec<Vec<u8>> {
+    vec![b"fee_collector".to_vec()]
+}
+
+pub fn sequence_seeds(emitter: &[u8; 32]) -> Vec<Vec<u8>> {
+    vec![b"Sequence".to_vec(), emitter.to_vec()]
+}
+
+// the posted vaa hash is the single keccak256 of the vaa body
+pub fn posted_vaa_seeds(vaa_hash: &[u8; 32]) -> Vec<Vec<u8>> {
+    vec![b"PostedVAA".to_vec(), vaa_hash.to_vec()]
+}
+
+// claims live under the program that redeems the vaa
+pub fn claim_seeds(emitter_chain: u16, emitter_address: &Address, sequence: u64) -> Vec<Vec<u8>> {
+    vec![emitter_address.0.to_vec(), emitter_chain.to_be_bytes().to_vec(), sequence.to_be_bytes().to_vec()]
+}
+
+// the account whose existence marks a VAA as redeemed by the program
+pub fn claim_address(program: &str, emitter_chain: u16, emitter_address: &Address, sequence: u64) -> Result<[u8; 32], CooError> {
+    let (claim, _) = derive_address(&claim_seeds(emitter_chain, emitter_address, sequence), program)?;
+    Ok(claim)
+}
+
+pub fn emitter_seeds() -> Vec<Vec<u8>> {
+    vec![b"emitter".to_vec()]
+}
+
+pub fn wrapped_mint_seeds(token_chain: u16, token_address: &Address) -> Vec<Vec<u8>> {
+    vec![b"wrapped".to_vec(), token_chain.to_be_bytes().to_vec(), token_address.0.to_vec()]
+}
+
+pub fn wrapped_meta_seeds(mint: &[u8; 32]) -> Vec<Vec<u8>> {
+    vec![b"meta".to_vec(), mint.to_vec()]
+}
+
+pub fn custody_seeds(mint: &[u8; 32]) -> Vec<Vec<u8>> {
+    vec![mint.to_vec()]
+}
+
+pub fn derive_address(seeds: &[Vec<u8>], program: &str) -> Result<([u8; 32], u8), CooError> {
+    let program_id = base58topubkey(program)?;
+    let seeds: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    find_program_address(&seeds, &program_id)
+}
+
+fn solana_rpc(rpc_url: &str, method: &str, params: serde_json::Value) -> Result<serde_json::Value, CooError> {
+    let request = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let client = reqwest::blocking::Client::new();
+    let response: serde_json::Value = client.post(rpc_url).json(&request).send()?.json()?;
+    if let Some(error) = response.get("error") {
+        return Err(CooError::ParseError(format!("{} failed: {}", method, error)));
+    }
+    return Ok(response["result"].clone());
+}
+
+pub fn solana_account_exists(rpc_url: &str, address: &[u8; 32]) -> Result<bool, CooError> {
+    let result = solana_rpc(rpc_url, "getAccountInfo", serde_json::json!([bytestobase58(address), { "encoding": "base64" }]))?;
+    return Ok(!result["value"].is_null());
+}
+
+// a claim account is only ever written by the redeeming transaction, so its oldest signature is the redemption
+pub fn solana_first_signature(rpc_url: &str, address: &[u8; 32]) -> Result<Option<String>, CooError> {
+    let result = solana_rpc(rpc_url, "getSignaturesForAddress", serde_json::json!([bytestobase58(address)]))?;
+    let signature = result.as_array()
+        .and_then(|signatures| signatures.last())
+        .and_then(|s| s["signature"].as_str())
+        .map(|s| s.to_string());
+    return Ok(signature);
+}
+
+pub fn pretty_program_address(program: &str, address: &[u8; 32], bump: u8) -> String {
+    let mut table = Table::new();
+    table.set_header(["Program Derived Address"]);
+    let rows: Vec<Row> = vec![
+        ["Program", program].into(),
+        ["Address", &bytestobase58(address)].into(),
+        ["Address (hex)", &hex::encode(address)].into(),
+        ["Bump", &bump.to_string()].into(),
+    ];
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostedAccountKind {
+    PostedVaa,
+    PostedMessage,
+    PostedMessageUnreliable,
+}
+
+// PostedVAA / PostedMessage account data of the solana core bridge
+#[derive(Debug, Clone)]
+pub struct PostedAccount<'a> {
+    pub kind: PostedAccountKind,
+    pub vaa_version: u8,
+    pub consistency_level: u8,
+    pub vaa_time: u32,
+    pub vaa_signature_account: [u8; 32],
+    pub submission_time: u32,
+    pub nonce: u32,
+    pub sequence: u64,
+    pub emitter_chain: u16,
+    pub emitter_address: Address,
+    pub payload: &'a [u8],
+}
+
+pub fn parse_posted_account(data: &[u8]) -> Result<PostedAccount, CooError> {
+    let mut reader = PayloadReader::new(data);
+    let kind = match reader.read_bytes(3)? {
+        b"vaa" => PostedAccountKind::PostedVaa,
+        b"msg" => PostedAccountKind::PostedMessage,
+        b"msu" => PostedAccountKind::PostedMessageUnreliable,
+        magic => return Err(CooError::ParseError(format!("not a posted vaa or message account, magic: {}", hex::encode(magic)))),
+    };
+    let vaa_version = reader.read_u8()?;
+    let consistency_level = reader.read_u8()?;
+    let vaa_time = reader.read_u32_le()?;
+    let vaa_signature_account = reader.read_array()?;
+    let submission_time = reader.read_u32_le()?;
+    let nonce = reader.read_u32_le()?;
+    let sequence = reader.read_u64_le()?;
+    let emitter_chain = reader.read_u16_le()?;
+    let emitter_address = reader.read_address()?;
+    let payload_len = reader.read_u32_le()? as usize;
+    let payload = reader.read_bytes(payload_len)?;
+    return Ok(PostedAccount {
+        kind,
+        vaa_version,
+        consistency_level,
+        vaa_time,
+        vaa_signature_account,
+        submission_time,
+        nonce,
+        sequence,
+        emitter_chain,
+        emitter_address,
+        payload,
+    });
+}
+
+impl<'a> PostedAccount<'a> {
+    // the account doesn't carry the guardian signatures, so the vaa has none. the guardian set index is only stored in
+    // the signature set account of a posted vaa (and messages are not signed yet), so it defaults to 0
+    pub fn to_vaa(&self, guardian_set_index: Option<u32>) -> Vaa<&'a RawMessage> {
+        let guardian_set_index = guardian_set_index.unwrap_or(0);
+        // vaa_time is only set once the vaa is posted, messages are timestamped with their submission time
+        let timestamp = match self.kind {
+            PostedAccountKind::PostedVaa => self.vaa_time,
+            PostedAccountKind::PostedMessage | PostedAccountKind::PostedMessageUnreliable => self.submission_time,
+        };
+        Vaa {
+            version: self.vaa_version,
+            guardian_set_index,
+            signatures: Vec::new(),
+            timestamp,
+            nonce: self.nonce,
+            emitter_chain: Chain::from(self.emitter_chain),
+            emitter_address: self.emitter_address,
+            sequence: self.sequence,
+            consistency_level: self.consistency_level,
+            payload: RawMessage::new(self.payload),
+        }
+    }
+}
+
+pub fn pretty_posted_account(account: &PostedAccount) -> String {
+    let mut table = Table::new();
+    table.set_header(["Solana Account Information"]);
+    let rows: Vec<Row> = vec![
+        ["Account Type", &format!("{:?}", account.kind)].into(),
+        ["Signature Account", &bytestobase58(&account.vaa_signature_account)].into(),
+        ["Submission Time", &account.submission_time.to_string()].into(),
+    ];
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_token_bridge_emitter() {
+        let (address, _) = derive_address(&emitter_seeds(), TOKEN_BRIDGE_PROGRAM).unwrap();
+        // the well known emitter of the solana token bridge
+        assert_eq!(hex::encode(address), "ec7372995d5cc8732397fb0ad35c0121e0eaa90d26f828a534cab54391b3a4f5");
+    }
+
+    #[test]
+    fn test_claim_address() {
+        // claim of the first transfer out of the ethereum token bridge
+        let emitter_address = hextoaddress("3ee18B2214AFF97000D974cf647E7C347E8fa585").unwrap();
+        let claim = claim_address(TOKEN_BRIDGE_PROGRAM, 2, &emitter_address, 1).unwrap();
+        assert_eq!(hex::encode(claim), "2870d3b20e8751d58fbde16d038274936d9cb1f9957d62bfc2cb80692bbe304d");
+    }
+
+    #[test]
+    fn test_parse_posted_vaa_account() {
+        let mut data = b"vaa".to_vec();
+        data.extend_from_slice(&[1, 32]);
+        data.extend_from_slice(&100u32.to_le_bytes());
+        data.extend_from_slice(&[9u8; 32]);
+        data.extend_from_slice(&101u32.to_le_bytes());
+        data.extend_from_slice(&7u32.to_le_bytes());
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&[3u8; 32]);
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&[0xbe, 0xef]);
+
+        let account = parse_posted_account(&data).unwrap();
+        assert_eq!(account.kind, PostedAccountKind::PostedVaa);
+        assert_eq!(account.to_vaa(None).guardian_set_index, 0);
+        let vaa = account.to_vaa(Some(4));
+        assert_eq!(vaa.sequence, 42);
+        assert_eq!(vaa.emitter_chain, Chain::Ethereum);
+        assert_eq!(vaa.timestamp, 100);
+        assert_eq!(vaa.guardian_set_index, 4);
+        assert!(vaa.signatures.is_empty());
+        assert_eq!(&vaa.payload[..], &[0xbe, 0xef]);
+
+        // a message account has no vaa time yet, the guardians use the submission time
+        data[..3].copy_from_slice(b"msg");
+        data[5..9].copy_from_slice(&0u32.to_le_bytes());
+        let account = parse_posted_account(&data).unwrap();
+        assert_eq!(account.kind, PostedAccountKind::PostedMessage);
+        assert_eq!(account.to_vaa(Some(4)).timestamp, 101);
+    }
+}
@@ -0,0 +1,188 @@
+use comfy_table::{Table, Row};
+use ethers::abi::Token;
+use ethers::types::{Address as EvmAddress, U256};
+use wormhole_sdk::{Address, Chain};
+
+use crate::common::*;
+use crate::evm::{encode_call, evm_contract, evm_provider, evm_wrapped_asset};
+use crate::solana::{derive_address, wrapped_mint_seeds, TOKEN_BRIDGE_PROGRAM};
+
+// address of the token that a transfer of (token_chain, token_address) pays out on chain, None if it was never attested there
+pub fn wrapped_asset_address(chain: Chain, token_chain: Chain, token_address: &Address) -> Result<Option<String>, CooError> {
+    if chain == token_chain {
+        // the native token is released from custody rather than minted
+        return Ok(Some(format!("{} (native)", token_address)));
+    }
+    match chain {
+        Chain::Solana => {
+            // the mint is derived offline, so it is returned even if the token was never attested
+            let (mint, _) = derive_address(&wrapped_mint_seeds(u16::from(token_chain), token_address), TOKEN_BRIDGE_PROGRAM)?;
+            Ok(Some(bytestobase58(&mint)))
+        },
+        _ => {
+            let provider = evm_provider(chain.into(), None)?;
+            let bridge = evm_contract(chain.into(), EmitterType::TokenBridge, None)?;
+            let wrapped = evm_wrapped_asset(provider, bridge, u16::from(token_chain), token_address)?;
+            Ok(wrapped.map(|w| format!("{:?}", w)))
+        },
+    }
+}
+
+// every chain that we know the token bridge of
+pub fn token_bridge_chains() -> Vec<Chain> {
+    let mut chains: Vec<Chain> = EMITTERS.keys()
+        .filter(|(_, emitter)| *emitter == EmitterType::TokenBridge)
+        .map(|(chain, _)| Chain::from(*chain))
+        .collect();
+    chains.push(Chain::Solana);
+    chains.sort_by_key(|c| u16::from(*c));
+    chains.dedup();
+    return chains;
+}
+
+// recipients are hex everywhere but on solana, where base58 is accepted as well
+pub fn recipienttoaddress(chain: Chain, s: &str) -> Result<Address, CooError> {
+    match (chain, hextoaddress(s)) {
+        (_, Ok(address)) => Ok(address),
+        (Chain::Solana, Err(_)) => Ok(Address(crate::solana::base58topubkey(s)?)),
+        (_, Err(e)) => Err(e),
+    }
+}
+
+// which token bridge call a transfer goes through
+pub struct TransferCall {
+    pub token: Option<EvmAddress>,
+    pub amount: U256,
+    pub recipient_chain: u16,
+    pub recipient: Address,
+    pub fee: U256,
+    pub nonce: u32,
+    pub payload: Option<Vec<u8>>,
+}
+
+// calldata and whether the amount itself is sent as value (native transfers)
+pub fn transfer_calldata(call: &TransferCall) -> (Vec<u8>, bool) {
+    let recipient_chain = Token::Uint(call.recipient_chain.into());
+    let recipient = Token::FixedBytes(call.recipient.0.to_vec());
+    let amount = Token::Uint(call.amount);
+    let fee = Token::Uint(call.fee);
+    let nonce = Token::Uint(call.nonce.into());
+    match (call.token, &call.payload) {
+        (Some(token), None) => (encode_call(
+            "transferTokens(address,uint256,uint16,bytes32,uint256,uint32)",
+            &[Token::Address(token), amount, recipient_chain, recipient, fee, nonce],
+        ), false),
+        (Some(token), Some(payload)) => (encode_call(
+            "transferTokensWithPayload(address,uint256,uint16,bytes32,uint32,bytes)",
+            &[Token::Address(token), amount, recipient_chain, recipient, nonce, Token::Bytes(payload.clone())],
+        ), false),
+        (None, None) => (encode_call(
+            "wrapAndTransferETH(uint16,bytes32,uint256,uint32)",
+            &[recipient_chain, recipient, fee, nonce],
+        ), true),
+        (None, Some(payload)) => (encode_call(
+            "wrapAndTransferETHWithPayload(uint16,bytes32,uint32,bytes)",
+            &[recipient_chain, recipient, nonce, Token::Bytes(payload.clone())],
+        ), true),
+    }
+}
+
+pub fn approve_calldata(spender: EvmAddress, amount: U256) -> Vec<u8> {
+    encode_call("approve(address,uint256)", &[Token::Address(spender), Token::Uint(amount)])
+}
+
+pub fn attest_calldata(token: EvmAddress, nonce: u32) -> Vec<u8> {
+    encode_call("attestToken(address,uint32)", &[Token::Address(token), Token::Uint(nonce.into())])
+}
+
+pub fn pretty_wrapped_assets(token_chain: Chain, token_address: &Address, wrapped: &[(Chain, Result<Option<String>, CooError>)]) -> String {
+    let mut table = Table::new();
+    table.set_header(["Target Chain", "Wrapped Address"]);
+    let mut rows: Vec<Row> = vec![["Origin", &format!("{} on {}", token_address, token_chain)].into()];
+    for (chain, address) in wrapped {
+        let address = match address {
+            Ok(Some(a)) => a.clone(),
+            Ok(None) => "not attested".to_string(),
+            Err(e) => format!("error: {:?}", e),
+        };
+        rows.push([chain.to_string(), address].into());
+    }
+    table.add_rows(rows);
+    return format!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::{decode, ParamType};
+    use super::*;
+
+    fn transfer_call(token: Option<EvmAddress>, payload: Option<Vec<u8>>) -> TransferCall {
+        TransferCall {
+            token,
+            amount: U256::from(1000),
+            recipient_chain: 1,
+            recipient: Address([0x11; 32]),
+            fee: U256::from(5),
+            nonce: 42,
+            payload,
+        }
+    }
+
+    #[test]
+    fn test_transfer_calldata() {
+        let token = EvmAddress::from([0x22; 20]);
+        let recipient_chain = Token::Uint(1.into());
+        let recipient = Token::FixedBytes(vec![0x11; 32]);
+        let nonce = Token::Uint(42.into());
+        let payload = vec![0xde, 0xad];
+
+        let (calldata, native) = transfer_calldata(&transfer_call(Some(token), None));
+        assert!(!native);
+        assert_eq!(hex::encode(&calldata[..4]), "0f5287b0");
+        let params = [ParamType::Address, ParamType::Uint(256), ParamType::Uint(16), ParamType::FixedBytes(32), ParamType::Uint(256), ParamType::Uint(32)];
+        assert_eq!(decode(&params, &calldata[4..]).unwrap(), vec![
+            Token::Address(token), Token::Uint(1000.into()), recipient_chain.clone(), recipient.clone(), Token::Uint(5.into()), nonce.clone(),
+        ]);
+
+        // the relayer fee is not part of transfers with a payload
+        let (calldata, native) = transfer_calldata(&transfer_call(Some(token), Some(payload.clone())));
+        assert!(!native);
+        assert_eq!(hex::encode(&calldata[..4]), "c5a5ebda");
+        let params = [ParamType::Address, ParamType::Uint(256), ParamType::Uint(16), ParamType::FixedBytes(32), ParamType::Uint(32), ParamType::Bytes];
+        assert_eq!(decode(&params, &calldata[4..]).unwrap(), vec![
+            Token::Address(token), Token::Uint(1000.into()), recipient_chain.clone(), recipient.clone(), nonce.clone(), Token::Bytes(payload.clone()),
+        ]);
+
+        // native transfers send the amount as value
+        let (calldata, native) = transfer_calldata(&transfer_call(None, None));
+        assert!(native);
+        assert_eq!(hex::encode(&calldata[..4]), "9981509f");
+        let params = [ParamType::Uint(16), ParamType::FixedBytes(32), ParamType::Uint(256), ParamType::Uint(32)];
+        assert_eq!(decode(&params, &calldata[4..]).unwrap(), vec![
+            recipient_chain.clone(), recipient.clone(), Token::Uint(5.into()), nonce.clone(),
+        ]);
+
+        let (calldata, native) = transfer_calldata(&transfer_call(None, Some(payload.clone())));
+        assert!(native);
+        assert_eq!(hex::encode(&calldata[..4]), "bee9cdfc");
+        let params = [ParamType::Uint(16), ParamType::FixedBytes(32), ParamType::Uint(32), ParamType::Bytes];
+        assert_eq!(decode(&params, &calldata[4..]).unwrap(), vec![
+            recipient_chain, recipient, nonce, Token::Bytes(payload),
+        ]);
+
+        assert_eq!(hex::encode(&approve_calldata(token, U256::from(1000))[..4]), "095ea7b3");
+    }
+
+    #[test]
+    fn test_recipienttoaddress() {
+        let mut expected = [0u8; 32];
+        expected[12..].copy_from_slice(&[0x22; 20]);
+        let evm_recipient = format!("0x{}", hex::encode([0x22; 20]));
+        assert_eq!(recipienttoaddress(Chain::Ethereum, &evm_recipient).unwrap(), Address(expected));
+
+        let solana_recipient = bytestobase58(&[0xaa; 32]);
+        assert_eq!(recipienttoaddress(Chain::Solana, &solana_recipient).unwrap(), Address([0xaa; 32]));
+        // base58 is only accepted for solana recipients
+        assert!(recipienttoaddress(Chain::Ethereum, &solana_recipient).is_err());
+    }
+}
@@ -1,16 +1,22 @@
 
 use reqwest::Url;
 use comfy_table::{Table, Row};
-use wormhole_sdk::Vaa;
+use wormhole_sdk::{Vaa, Address, Chain};
 use serde_wormhole::RawMessage;
 
 // use ethers::providers::{Middleware, Provider, Http};
 
 use crate::common::*;
+use crate::gateway::{is_gateway_transfer, wormchaintobech32, GATEWAY_CHAIN};
+use crate::inner_payload::{decode_inner_payloads, pretty_inner_payloads};
 
 pub fn query_guardian(chain: CooChain, emitter: EmitterType, sequence: u64, guardian_url: Url) -> Result<Vec<u8>, CooError> {
     let query_url = get_query_url(chain, emitter, sequence, guardian_url)?;
     println!("querying guardian at {}", query_url);
+    return fetch_signed_vaa(query_url);
+}
+
+fn fetch_signed_vaa(query_url: Url) -> Result<Vec<u8>, CooError> {
     // make a http request to the guardian
     let result = reqwest::blocking::get(query_url)?;
     let body = result.text()?;
@@ -21,6 +27,10 @@ pub fn query_guardian(chain: CooChain, emitter: EmitterType, sequence: u64, guar
         Some(v) => {
             v
         },
+        // grpc NOT_FOUND, the guardian has not seen (or not yet signed) the message
+        None if guardian_response["code"].as_u64() == Some(5) => {
+            return Err(CooError::VaaNotFound(guardian_response["message"].as_str().unwrap_or_default().to_string()));
+        },
         None => {
             return Err(CooError::ParseError(format!("vaaBytes not found in response: {}", body)));
         }
@@ -29,6 +39,22 @@ pub fn query_guardian(chain: CooChain, emitter: EmitterType, sequence: u64, guar
     return Ok(vaa_bytes);
 }
 
+// the guardians only serve the VAA once the message is final and has reached quorum. only a missing VAA and failed
+// requests are retried, anything else will not go away by waiting
+pub fn wait_for_vaa(chain: CooChain, emitter: EmitterType, sequence: u64, guardian_url: Url, timeout: std::time::Duration) -> Result<Vec<u8>, CooError> {
+    let query_url = get_query_url(chain, emitter, sequence, guardian_url)?;
+    println!("waiting for the VAA at {}", query_url);
+    let started = std::time::Instant::now();
+    loop {
+        match fetch_signed_vaa(query_url.clone()) {
+            Ok(vaa_bytes) => return Ok(vaa_bytes),
+            Err(e) if started.elapsed() >= timeout => return Err(e),
+            Err(CooError::VaaNotFound(_)) | Err(CooError::ReqwestError(_)) => std::thread::sleep(std::time::Duration::from_secs(5)),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 pub fn get_query_url(chain: CooChain, emitter: EmitterType, sequence: u64, guardian_url: Url) -> Result<Url, CooError> {
     let emitter_contract = resolve_emitter_address(chain, emitter)?;
     let query_path = format!("v1/signed_vaa/{}/{}/{}", u16::from(chain), emitter_contract, sequence);
@@ -41,17 +67,300 @@ pub fn parse_vaa<'a> (vaa_bytes: &'a [u8]) -> Result<Vaa<&'a RawMessage>, CooErr
     return Ok(vaa);
 }
 
+// header fields of a VAA that we are building locally
+#[derive(Debug, Clone, Copy)]
+pub struct CooVaaHeader {
+    pub chain: CooChain,
+    pub emitter_address: Address,
+    pub sequence: u64,
+    pub nonce: u32,
+    pub timestamp: u32,
+    pub consistency_level: u8,
+    pub guardian_set_index: u32,
+}
+
+pub fn build_vaa<'a> (header: &CooVaaHeader, payload: &'a [u8]) -> Vaa<&'a RawMessage> {
+    // signatures are filled in afterwards by the signer
+    return Vaa {
+        version: 1,
+        guardian_set_index: header.guardian_set_index,
+        signatures: Vec::new(),
+        timestamp: header.timestamp,
+        nonce: header.nonce,
+        emitter_chain: header.chain.into(),
+        emitter_address: header.emitter_address,
+        sequence: header.sequence,
+        consistency_level: header.consistency_level,
+        payload: RawMessage::new(payload),
+    };
+}
+
+// a header for tests that don't care about the header fields
+#[cfg(test)]
+pub fn test_vaa_header() -> CooVaaHeader {
+    CooVaaHeader {
+        chain: CooChain::Inner(Chain::Ethereum),
+        emitter_address: Address([1u8; 32]),
+        sequence: 1,
+        nonce: 0,
+        timestamp: 1,
+        consistency_level: 1,
+        guardian_set_index: 0,
+    }
+}
+
+pub fn encode_token_payload(message: &wormhole_sdk::token::Message<&RawMessage>) -> Result<Vec<u8>, CooError> {
+    let payload = serde_wormhole::to_vec(message)?;
+    return Ok(payload);
+}
+
+pub fn encode_nft_payload(message: &wormhole_sdk::nft::Message) -> Result<Vec<u8>, CooError> {
+    let payload = serde_wormhole::to_vec(message)?;
+    return Ok(payload);
+}
+
+pub fn infer_payload_type(vaa: &Vaa<&RawMessage>) -> PayloadType {
+    // we'll first check out what is the emitter address, and from there we will know if it is one of the known contracts
+    // if it is, we'll decode the payload accordingly
+
+    let emitter_address = vaa.emitter_address.to_string().to_lowercase();
+    // emitter_address is a 0 left-padded hex string in lower case.
+    // we'll perform the needed transformation from the map
+    let key = EMITTERS.iter().find_map(|(k, v)| { 
+            let map_entry = format!("{:0>64}", v).to_lowercase();
+            if emitter_address == map_entry {
+                Some(k)
+            } else {
+                None
+            }
+    });
+    match key {
+        Some((_, emitter)) => {
+            match emitter {
+                EmitterType::Unset => unreachable!("unset should not be in the map"),
+                EmitterType::Address(_) => unreachable!("address should not be in the map"),
+                EmitterType::TokenBridge => {
+                    // we'll check the payload type from the first byte
+                    let payload_type = vaa.payload[0];
+                    match payload_type {
+                        0x01 => PayloadType::WormholeTokenTransfer,
+                        0x02 => PayloadType::WormholeAssetMeta,
+                        // transfers to wormhole gateway are bridged further into cosmos
+                        0x03 => match decode_wormhole_token(vaa) {
+                            Ok(wormhole_sdk::token::Message::TransferWithPayload { recipient, recipient_chain, .. })
+                                if is_gateway_transfer(recipient_chain, &recipient) => PayloadType::WormholeGatewayTransfer,
+                            _ => PayloadType::WormholeTokenTransferPayload,
+                        },
+                        // we're not really sure what this is, so raw bytes it shall be.
+                        _ => PayloadType::RawBytes
+                    }
+                },
+                EmitterType::NftBridge => {
+                    // we'll check the payload type from the first byte
+                    let payload_type = vaa.payload[0];
+                    match payload_type {
+                        0x01 => PayloadType::WormholeNftTransfer,
+                        // we're not really sure what this is, so raw bytes it shall be.
+                        _ => PayloadType::RawBytes,
+                    }
+                }
+                EmitterType::WormholeRelayer => {
+                    // we'll check the payload type from the first byte
+                    let payload_type = vaa.payload[0];
+                    match payload_type {
+                        0x01 => PayloadType::WormholeRelayerDelivery,
+                        0x02 => PayloadType::WormholeRelayerRedelivery,
+                        // we're not really sure what this is, so raw bytes it shall be.
+                        _ => PayloadType::RawBytes,
+                    }
+                },
+                EmitterType::CctpIntegration => {
+                    // we'll check the payload type from the first byte
+                    let payload_type = vaa.payload[0];
+                    match payload_type {
+                        0x01 => PayloadType::WormholeCctpDeposit,
+                        // we're not really sure what this is, so raw bytes it shall be.
+                        _ => PayloadType::RawBytes,
+                    }
+                },
+                EmitterType::Pyth => {
+                    // pyth messages start with a 4 byte magic instead of a payload id
+                    if vaa.payload.starts_with(&crate::pyth::P2W_MAGIC) {
+                        PayloadType::PythBatchPriceAttestation
+                    } else if vaa.payload.starts_with(&crate::pyth::ACCUMULATOR_WORMHOLE_MAGIC) {
+                        PayloadType::PythAccumulatorUpdate
+                    } else {
+                        // we're not really sure what this is, so raw bytes it shall be.
+                        PayloadType::RawBytes
+                    }
+                },
+                // currently corebridge have governance stuff, so we'll just leave it as raw bytes
+                EmitterType::CoreBridge => PayloadType::RawBytes, 
+            }
+        },
+        // ntt managers are deployed per token, so we recognize their messages by prefix instead
+        None if vaa.payload.starts_with(&crate::ntt::WH_TRANSCEIVER_PAYLOAD_PREFIX) => PayloadType::WormholeNttTransceiverMessage,
+        None if vaa.payload.starts_with(&crate::ntt::WH_TRANSCEIVER_INIT_PREFIX) => PayloadType::WormholeNttTransceiverInit,
+        None if vaa.payload.starts_with(&crate::ntt::WH_TRANSCEIVER_REGISTRATION_PREFIX) => PayloadType::WormholeNttTransceiverRegistration,
+        // not one of the known emitters, so raw bytes it shall be.
+        None => PayloadType::RawBytes,
+    }
+}
+
+fn parse_field<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, CooError> {
+    value.parse::<T>().map_err(|_| CooError::ParseError(format!("invalid value for {}: {}", key, value)))
+}
+
+// applies an edit to a header field of the VAA, returns false if the key is not a header field
+pub fn edit_vaa_header<P>(vaa: &mut Vaa<P>, key: &str, value: &str) -> Result<bool, CooError> {
+    match key {
+        "version" => vaa.version = parse_field(key, value)?,
+        "guardian_set_index" => vaa.guardian_set_index = parse_field(key, value)?,
+        "timestamp" => vaa.timestamp = parse_field(key, value)?,
+        "nonce" => vaa.nonce = parse_field(key, value)?,
+        "emitter_chain" => vaa.emitter_chain = CooChain::from(value).into(),
+        "emitter_address" => vaa.emitter_address = hextoaddress(value)?,
+        "sequence" => vaa.sequence = parse_field(key, value)?,
+        "consistency_level" => vaa.consistency_level = parse_field(key, value)?,
+        _ => return Ok(false),
+    }
+    return Ok(true);
+}
+
+// applies edits to the payload of the VAA according to its payload type and returns the re-encoded payload
+pub fn edit_vaa_payload(payload_type: PayloadType, payload: &[u8], edits: &[(&str, &str)]) -> Result<Vec<u8>, CooError> {
+    // nothing to change, so we keep the payload byte for byte
+    if edits.is_empty() {
+        return Ok(payload.to_vec());
+    }
+    // for transfers with payload, `payload` refers to the inner payload, otherwise to the entire payload
+    let new_payload = match edits.iter().find(|(k, _)| *k == "payload") {
+        Some((_, v)) => Some(hextobytes(v)?),
+        None => None,
+    };
+    let unknown_field = |key: &str| CooError::ParseError(format!("unknown field for {:?} payload: {}", payload_type, key));
+
+    match payload_type {
+        PayloadType::SmartInfer => unreachable!("smart infer should have been handled by the caller"),
+        PayloadType::RawBytes => {
+            if let Some((key, _)) = edits.iter().find(|(k, _)| *k != "payload") {
+                return Err(unknown_field(key));
+            }
+            return Ok(new_payload.unwrap_or_else(|| payload.to_vec()));
+        },
+        PayloadType::WormholeTokenTransfer | PayloadType::WormholeAssetMeta | PayloadType::WormholeTokenTransferPayload | PayloadType::WormholeGatewayTransfer => {
+            let mut message: wormhole_sdk::token::Message<&RawMessage> = serde_wormhole::from_slice(payload)?;
+            for (key, value) in edits {
+                let key = *key;
+                match &mut message {
+                    wormhole_sdk::token::Message::Transfer { amount, token_address, token_chain, recipient, recipient_chain, fee } => {
+                        match key {
+                            "amount" => *amount = stringtoamount(value)?,
+                            "token_address" => *token_address = hextoaddress(value)?,
+                            "token_chain" => *token_chain = CooChain::from(*value).into(),
+                            "recipient" => *recipient = hextoaddress(value)?,
+                            "recipient_chain" => *recipient_chain = CooChain::from(*value).into(),
+                            "fee" => *fee = stringtoamount(value)?,
+                            _ => return Err(unknown_field(key)),
+                        }
+                    },
+                    wormhole_sdk::token::Message::AssetMeta { token_address, token_chain, decimals, symbol, name } => {
+                        match key {
+                            "token_address" => *token_address = hextoaddress(value)?,
+                            "token_chain" => *token_chain = CooChain::from(*value).into(),
+                            "decimals" => *decimals = parse_field(key, value)?,
+                            "symbol" => *symbol = parse_field(key, value)?,
+                            "name" => *name = parse_field(key, value)?,
+                            _ => return Err(unknown_field(key)),
+                        }
+                    },
+                    wormhole_sdk::token::Message::TransferWithPayload { amount, token_address, token_chain, recipient, recipient_chain, sender_address, payload } => {
+                        match key {
+                            "amount" => *amount = stringtoamount(value)?,
+                            "token_address" => *token_address = hextoaddress(value)?,
+                            "token_chain" => *token_chain = CooChain::from(*value).into(),
+                            "recipient" => *recipient = hextoaddress(value)?,
+                            "recipient_chain" => *recipient_chain = CooChain::from(*value).into(),
+                            "sender_address" => *sender_address = hextoaddress(value)?,
+                            "payload" => *payload = RawMessage::new(new_payload.as_ref().unwrap()),
+                            _ => return Err(unknown_field(key)),
+                        }
+                    },
+                }
+            }
+            return encode_token_payload(&message);
+        },
+        PayloadType::WormholeRelayerDelivery | PayloadType::WormholeRelayerRedelivery | PayloadType::WormholeCctpDeposit |
+        PayloadType::WormholeNttTransceiverMessage | PayloadType::WormholeNttTransceiverInit | PayloadType::WormholeNttTransceiverRegistration |
+        PayloadType::PythBatchPriceAttestation | PayloadType::PythAccumulatorUpdate | PayloadType::CustomSchema | PayloadType::WasmPlugin => {
+            return Err(CooError::ParseError(format!("editing {:?} payloads is not supported, use payload=<hex> with --payload-type raw-bytes", payload_type)));
+        },
+        PayloadType::WormholeNftTransfer => {
+            let mut message = decode_nft_payload(payload)?;
+            for (key, value) in edits {
+                let key = *key;
+                match &mut message {
+                    wormhole_sdk::nft::Message::Transfer { nft_address, nft_chain, symbol, name, token_id, uri, to, to_chain } => {
+                        match key {
+                            "nft_address" => *nft_address = hextoaddress(value)?,
+                            "nft_chain" => *nft_chain = CooChain::from(*value).into(),
+                            "symbol" => *symbol = parse_field(key, value)?,
+                            "name" => *name = parse_field(key, value)?,
+                            "token_id" => *token_id = wormhole_sdk::nft::TokenId(stringtoamount(value)?.0),
+                            "uri" => *uri = parse_field(key, value)?,
+                            "to" => *to = hextoaddress(value)?,
+                            "to_chain" => *to_chain = CooChain::from(*value).into(),
+                            _ => return Err(unknown_field(key)),
+                        }
+                    },
+                }
+            }
+            return encode_nft_payload(&message);
+        },
+    }
+}
+
+// the chain and bridge that a transfer VAA is redeemed on
+pub fn transfer_target(vaa: &Vaa<&RawMessage>, payload_type: PayloadType) -> Result<(Chain, EmitterType), CooError> {
+    match payload_type {
+        PayloadType::WormholeTokenTransfer |
+        PayloadType::WormholeTokenTransferPayload |
+        PayloadType::WormholeGatewayTransfer => match decode_wormhole_token(vaa)? {
+            wormhole_sdk::token::Message::Transfer { recipient_chain, .. } |
+            wormhole_sdk::token::Message::TransferWithPayload { recipient_chain, .. } => Ok((recipient_chain, EmitterType::TokenBridge)),
+            wormhole_sdk::token::Message::AssetMeta { .. } => Err(CooError::ParseError("asset meta VAAs have no target chain".to_string())),
+        },
+        PayloadType::WormholeNftTransfer => match decode_wormhole_nft(vaa)? {
+            wormhole_sdk::nft::Message::Transfer { to_chain, .. } => Ok((to_chain, EmitterType::NftBridge)),
+        },
+        _ => Err(CooError::ParseError(format!("{:?} is not a token or NFT transfer", payload_type))),
+    }
+}
+
 pub fn decode_wormhole_token<'a> (vaa: &Vaa<&'a RawMessage>) -> Result<wormhole_sdk::token::Message, CooError> {
-    let message: wormhole_sdk::token::Message = serde_wormhole::from_slice(vaa.payload).unwrap();
+    let message: wormhole_sdk::token::Message = serde_wormhole::from_slice(vaa.payload)?;
     return Ok(message);
 }
 
 pub fn decode_wormhole_nft<'a> (vaa: &Vaa<&'a RawMessage>) -> Result<wormhole_sdk::nft::Message, CooError> {
-    let message: wormhole_sdk::nft::Message = serde_wormhole::from_slice(vaa.payload).unwrap();
+    decode_nft_payload(vaa.payload)
+}
+
+pub fn decode_nft_payload(payload: &[u8]) -> Result<wormhole_sdk::nft::Message, CooError> {
+    let message: wormhole_sdk::nft::Message = serde_wormhole::from_slice(payload)?;
     return Ok(message);
 }
 
-pub fn pretty_token_payload(payload: &wormhole_sdk::token::Message) -> String {
+fn pretty_recipient(recipient_chain: wormhole_sdk::Chain, recipient: &Address) -> String {
+    // wormchain addresses are only recognizable in their bech32 form
+    if u16::from(recipient_chain) == GATEWAY_CHAIN {
+        return format!("{} ({})", wormchaintobech32(recipient), recipient);
+    }
+    recipient.to_string()
+}
+
+// wrapped is the token the recipient receives on the destination chain, when it has been looked up
+pub fn pretty_token_payload(payload: &wormhole_sdk::token::Message, wrapped: Option<&str>) -> String {
     let mut table = Table::new();
     table.set_header(["Wormhole Token Payload Information"]);
     let rows:Vec<Row> = match payload {
@@ -61,7 +370,7 @@ pub fn pretty_token_payload(payload: &wormhole_sdk::token::Message) -> String {
                 ["Amount", &amounttostring(amount)].into(),
                 ["Token Address (Origin)", &token_address.to_string()].into(),
                 ["Token Chain (Origin)", &token_chain.to_string()].into(),
-                ["Token Recipient", &recipient.to_string()].into(),
+                ["Token Recipient", &pretty_recipient(*recipient_chain, recipient)].into(),
                 ["Token Recipient Chain", &recipient_chain.to_string()].into(),
                 ["Relayer Fees", &amounttostring(fee)].into(),
             ]
@@ -83,7 +392,7 @@ pub fn pretty_token_payload(payload: &wormhole_sdk::token::Message) -> String {
                 ["Amount", &amounttostring(amount)].into(),
                 ["Token Address (Origin)", &token_address.to_string()].into(),
                 ["Token Chain (Origin)", &token_chain.to_string()].into(),
-                ["Token Recipient", &recipient.to_string()].into(),
+                ["Token Recipient", &pretty_recipient(*recipient_chain, recipient)].into(),
                 ["Token Recipient Chain", &recipient_chain.to_string()].into(),
                 ["Sender Address", &sender_address.to_string()].into(),
                 ["Payload", &payload.to_string()].into(),
@@ -91,6 +400,14 @@ pub fn pretty_token_payload(payload: &wormhole_sdk::token::Message) -> String {
         },
     };
     table.add_rows(rows);
+    if let Some(wrapped) = wrapped {
+        table.add_row(["Wrapped on destination", wrapped]);
+    }
+    // the inner payload of a transfer with payload is rendered underneath the transfer
+    if let wormhole_sdk::token::Message::TransferWithPayload { recipient, recipient_chain, payload, .. } = payload {
+        let inner = decode_inner_payloads(*recipient_chain, recipient, payload);
+        return format!("{table}\n{}", pretty_inner_payloads(&inner));
+    }
     return format!("{table}");
 }
 
@@ -118,9 +435,14 @@ pub fn pretty_nft_payload(payload: &wormhole_sdk::nft::Message) -> String {
 }
 
 pub fn pretty_vaa<T>(vaa: &Vaa<T>) -> String {
-    let multiline_signatures = vaa.signatures.iter().map(
-        |s| format!("{: <2}: {}", s.index, hex::encode(s.signature))
-    ).collect::<Vec<String>>().join("\n");
+    // unsigned VAAs and solana accounts don't carry any signatures
+    let multiline_signatures = if vaa.signatures.is_empty() {
+        "absent".to_string()
+    } else {
+        vaa.signatures.iter().map(
+            |s| format!("{: <2}: {}", s.index, hex::encode(s.signature))
+        ).collect::<Vec<String>>().join("\n")
+    };
     let mut table = Table::new();
     table.set_header(["VAA Information"]);
     let rows:Vec<Row> = vec![
@@ -161,4 +483,40 @@ mod tests {
         let query_url = get_query_url(chain, EmitterType::TokenBridge, 1, guardian_url).unwrap();
         assert_eq!(query_url.to_string(), "https://wormhole-v2-mainnet-api.certus.one/v1/signed_vaa/6/0000000000000000000000000e082f06ff657d94310cb8ce8b0d9a04541d8052/1")
     }
+
+    #[test]
+    fn test_edit_and_resign_vaa() {
+        use crate::signer::{parse_guardian_keys, recover_guardian, sign_vaa, vaa_digest};
+
+        let transfer: wormhole_sdk::token::Message<&RawMessage> = wormhole_sdk::token::Message::Transfer {
+            amount: wormhole_sdk::Amount([1u8; 32]),
+            token_address: Address([2u8; 32]),
+            token_chain: Chain::Ethereum,
+            recipient: Address([3u8; 32]),
+            recipient_chain: Chain::Solana,
+            fee: wormhole_sdk::Amount([0u8; 32]),
+        };
+        let payload = encode_token_payload(&transfer).unwrap();
+        let keys = parse_guardian_keys(&[]).unwrap();
+        let mut vaa = build_vaa(&test_vaa_header(), &payload);
+        sign_vaa(&mut vaa, &keys).unwrap();
+        let original_digest = vaa_digest(&vaa).unwrap();
+
+        assert!(edit_vaa_header(&mut vaa, "sequence", "42").unwrap());
+        assert!(!edit_vaa_header(&mut vaa, "amount", "5").unwrap());
+        let new_payload = edit_vaa_payload(PayloadType::WormholeTokenTransfer, &payload, &[("amount", "5")]).unwrap();
+        vaa.payload = RawMessage::new(&new_payload);
+        sign_vaa(&mut vaa, &keys).unwrap();
+
+        let digest = vaa_digest(&vaa).unwrap();
+        assert_ne!(digest, original_digest);
+        assert_eq!(vaa.sequence, 42);
+        let edited: wormhole_sdk::token::Message = serde_wormhole::from_slice(&new_payload).unwrap();
+        match edited {
+            wormhole_sdk::token::Message::Transfer { amount, .. } => assert_eq!(amount.0[31], 5),
+            _ => panic!("expected a transfer"),
+        }
+        let recovered = recover_guardian(&digest, &vaa.signatures[0]).unwrap();
+        assert_eq!(hex::encode(recovered), "befa429d57cd18b7f8a4d91a2da9ab4af05d0fbe");
+    }
 }
\ No newline at end of file